@@ -0,0 +1,135 @@
+use crate::game_node::GameNode;
+use crate::game_preflop::{PreflopAction, PreflopNode};
+
+/// Returns the size of a raise to `amount` facing `bet` as a fraction of the pot after calling,
+/// where `dead` is the dead money in the pot.
+pub fn pot_fraction(amount: f64, bet: f64, dead: f64) -> f64 {
+    (amount - bet) / (2.0 * bet + dead)
+}
+
+/// Returns the probability of mapping a bet of `x` to the smaller size `a` rather than the larger
+/// size `b` (`a <= x <= b`, as fractions of the pot) by the pseudo-harmonic mapping of Ganzfried
+/// and Sandholm, which is exploitable by neither size of bets near `a` nor `b`.
+pub fn pseudo_harmonic(a: f64, b: f64, x: f64) -> f64 {
+    ((b - x) * (1.0 + a)) / ((b - a) * (1.0 + x))
+}
+
+/// Translates a raise of `size` (a fraction of the pot, see `pot_fraction`) to a mix of the two
+/// neighbouring actions of `node` among call (a raise of zero), the raise sizes and all-in.
+/// Returns the pairs of action and probability; sizes outside the tree map to the smallest action
+/// (call, or the smallest raise when limps are disabled) or all-in.
+pub fn translate_raise(node: &PreflopNode, size: f64) -> Vec<(usize, f64)> {
+    let sizes = (1..node.num_actions())
+        .map(|action| match node.action(action) {
+            PreflopAction::Call => (action, 0.0),
+            _ => {
+                let amount = node.play(action).bet();
+                (action, pot_fraction(amount, node.bet(), node.dead_money()))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match sizes.iter().position(|&(_, s)| s >= size - 1e-9) {
+        Some(0) => vec![(sizes[0].0, 1.0)],
+        Some(i) if (sizes[i].1 - size).abs() < 1e-9 => vec![(sizes[i].0, 1.0)],
+        Some(i) => {
+            let ((smaller, a), (larger, b)) = (sizes[i - 1], sizes[i]);
+            let prob = pseudo_harmonic(a, b, size);
+            vec![(smaller, prob), (larger, 1.0 - prob)]
+        }
+        None => vec![(sizes.last().unwrap().0, 1.0)],
+    }
+}
+
+/// Translates a raise to `multiple` times the current bet of `node` (see `translate_raise`).
+pub fn translate_multiple(node: &PreflopNode, multiple: f64) -> Vec<(usize, f64)> {
+    let size = pot_fraction(multiple * node.bet(), node.bet(), node.dead_money());
+    translate_raise(node, size)
+}
+
+/// Parses a raise such as `R2.2`, `b5` or `3.3x` and returns its multiple of the current bet.
+pub fn parse_raise(token: &str) -> Option<f64> {
+    let token = token.to_ascii_lowercase();
+    let multiple = token
+        .trim_start_matches(['r', 'b'])
+        .trim_end_matches('x')
+        .parse::<f64>()
+        .ok()?;
+    Some(multiple).filter(|&multiple| multiple > 1.0)
+}
+
+/// Returns the mix of `translate_raise` in words, e.g., `Bet 2.5x 64% / Call 36%`.
+pub fn mix_str(mix: &[(usize, f64)], labels: &[String]) -> String {
+    mix.iter()
+        .map(|&(action, prob)| format!("{} {:.0}%", labels[action], 100.0 * prob))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pseudo_harmonic_mapping() {
+        let (a, b) = (0.5, 1.0);
+        assert!((pseudo_harmonic(a, b, a) - 1.0).abs() < 1e-9);
+        assert!(pseudo_harmonic(a, b, b).abs() < 1e-9);
+
+        let probs = (0..=10)
+            .map(|i| pseudo_harmonic(a, b, a + (b - a) * i as f64 / 10.0))
+            .collect::<Vec<_>>();
+        assert!(probs.windows(2).all(|w| w[0] > w[1]));
+        assert!(probs.iter().all(|&prob| (0.0..=1.0).contains(&prob)));
+
+        // a call (a raise of zero) against a pot-sized raise
+        assert!((pseudo_harmonic(0.0, 1.0, 0.5) - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn translate() {
+        let root = PreflopNode::new(20.0);
+        let all_in = root.num_actions() - 1;
+        let size =
+            |action: usize| pot_fraction(root.play(action).bet(), root.bet(), root.dead_money());
+
+        assert_eq!(translate_raise(&root, 0.0), vec![(1, 1.0)]);
+        assert_eq!(translate_raise(&root, -0.1), vec![(1, 1.0)]);
+        assert_eq!(translate_raise(&root, size(2)), vec![(2, 1.0)]);
+        assert_eq!(translate_raise(&root, size(all_in)), vec![(all_in, 1.0)]);
+        assert_eq!(
+            translate_raise(&root, size(all_in) + 1.0),
+            vec![(all_in, 1.0)]
+        );
+
+        // between call and the smallest raise, and between two raises
+        for (smaller, larger) in [(1, 2), (2, 3)] {
+            let lo = if smaller == 1 { 0.0 } else { size(smaller) };
+            let mix = translate_raise(&root, (lo + size(larger)) / 2.0);
+            assert_eq!(mix.len(), 2);
+            assert_eq!((mix[0].0, mix[1].0), (smaller, larger));
+            assert!(mix[0].1 > 0.0 && mix[1].1 > 0.0);
+            assert!((mix[0].1 + mix[1].1 - 1.0).abs() < 1e-9);
+        }
+
+        assert_eq!(translate_multiple(&root, 2.5), vec![(2, 1.0)]);
+        let mix = translate_multiple(&root, 2.75);
+        assert_eq!((mix[0].0, mix[1].0), (2, 3));
+        assert!((mix[0].1 + mix[1].1 - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_raise("R2.2"), Some(2.2));
+        assert_eq!(parse_raise("b5"), Some(5.0));
+        assert_eq!(parse_raise("3.3x"), Some(3.3));
+        assert_eq!(parse_raise("r1"), None);
+        assert_eq!(parse_raise("call"), None);
+
+        let labels = ["Fold", "Call", "Bet 2.5x"].map(String::from);
+        assert_eq!(
+            mix_str(&[(2, 0.64), (1, 0.36)], &labels),
+            "Bet 2.5x 64% / Call 36%"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Forced bets of heads-up games. Player 0 is SB and player 1 is BB; each player posts `ante`, and
+/// BB additionally posts `bb_ante`. Antes are dead money, i.e., they do not count toward bets.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Blinds {
+    pub sb: f64,
+    pub bb: f64,
+    pub ante: f64,
+    pub bb_ante: f64,
+}
+
+impl Default for Blinds {
+    fn default() -> Self {
+        Self {
+            sb: 0.5,
+            bb: 1.0,
+            ante: 0.0,
+            bb_ante: 0.0,
+        }
+    }
+}
+
+impl Blinds {
+    /// Returns the blind posted by `player`.
+    #[inline]
+    pub fn blind(&self, player: usize) -> f64 {
+        [self.sb, self.bb][player]
+    }
+
+    /// Returns the dead money posted by `player`.
+    #[inline]
+    pub fn dead(&self, player: usize) -> f64 {
+        self.ante + [0.0, self.bb_ante][player]
+    }
+
+    /// Returns the tag appended to output file names (empty for the default blinds).
+    pub fn tag(&self) -> String {
+        let default = Self::default();
+        let mut ret = String::new();
+        for &(name, value, default) in &[
+            ("sb", self.sb, default.sb),
+            ("bb", self.bb, default.bb),
+            ("ante", self.ante, default.ante),
+            ("bbante", self.bb_ante, default.bb_ante),
+        ] {
+            if value != default {
+                ret.push_str(&format!("-{}{}", name, value));
+            }
+        }
+        ret
+    }
+
+    /// Parses a tag generated by `tag`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let re = Regex::new(r"-(sb|bb|ante|bbante)(\d+\.?\d*)").unwrap();
+        let mut ret = Self::default();
+        let mut len = 0;
+        for cs in re.captures_iter(tag) {
+            let value = cs[2].parse().ok()?;
+            match &cs[1] {
+                "sb" => ret.sb = value,
+                "bb" => ret.bb = value,
+                "ante" => ret.ante = value,
+                _ => ret.bb_ante = value,
+            }
+            len += cs[0].len();
+        }
+        if len == tag.len() {
+            Some(ret)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a human-readable description (empty for the default blinds).
+    pub fn description(&self) -> String {
+        let mut ret = Vec::new();
+        if self.sb != 0.5 || self.bb != 1.0 {
+            ret.push(format!("blinds {}/{}", self.sb, self.bb));
+        }
+        if self.ante != 0.0 {
+            ret.push(format!("ante {}", self.ante));
+        }
+        if self.bb_ante != 0.0 {
+            ret.push(format!("BB ante {}", self.bb_ante));
+        }
+        ret.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_round_trip() {
+        let cases = [
+            Blinds::default(),
+            Blinds {
+                ante: 0.1,
+                ..Blinds::default()
+            },
+            Blinds {
+                bb_ante: 1.0,
+                ..Blinds::default()
+            },
+            Blinds {
+                sb: 0.4,
+                bb: 0.8,
+                ante: 0.05,
+                bb_ante: 0.25,
+            },
+            Blinds {
+                sb: 1.0,
+                bb: 2.0,
+                ante: 0.0,
+                bb_ante: 0.0,
+            },
+        ];
+        for blinds in cases.iter() {
+            assert_eq!(Blinds::from_tag(&blinds.tag()), Some(*blinds));
+        }
+        assert_eq!(Blinds::default().tag(), "");
+        assert_eq!(cases[3].tag(), "-sb0.4-bb0.8-ante0.05-bbante0.25");
+    }
+
+    #[test]
+    fn invalid_tags() {
+        assert_eq!(Blinds::from_tag("-ante"), None);
+        assert_eq!(Blinds::from_tag("-straddle2"), None);
+        assert_eq!(Blinds::from_tag("ante0.1"), None);
+        assert_eq!(Blinds::from_tag("-ante0.1x"), None);
+    }
+
+    #[test]
+    fn description() {
+        assert_eq!(Blinds::default().description(), "");
+        let blinds = Blinds {
+            sb: 1.0,
+            bb: 2.0,
+            ante: 0.2,
+            bb_ante: 2.0,
+        };
+        assert_eq!(blinds.description(), "blinds 1/2, ante 0.2, BB ante 2");
+        let blinds = Blinds {
+            bb_ante: 1.0,
+            ..Blinds::default()
+        };
+        assert_eq!(blinds.description(), "BB ante 1");
+    }
+}
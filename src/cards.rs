@@ -0,0 +1,72 @@
+use crate::rng::Rng;
+use crate::showdown::hand_strength;
+
+/// Cards are numbered `4 * rank + suit`, where rank 0 is deuce and rank 12 is ace.
+pub const RANKS: [char; 13] = [
+    '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+];
+pub const SUITS: [char; 4] = ['c', 'd', 'h', 's'];
+
+pub fn card_str(card: usize) -> String {
+    format!("{}{}", RANKS[card / 4], SUITS[card % 4])
+}
+
+/// Parses a card such as `Ah` or `td`.
+pub fn parse_card(input: &str) -> Option<usize> {
+    let chars = input.chars().collect::<Vec<_>>();
+    match chars.as_slice() {
+        [rank, suit] => {
+            let rank = RANKS.iter().position(|&r| r == rank.to_ascii_uppercase())?;
+            let suit = SUITS.iter().position(|&s| s == suit.to_ascii_lowercase())?;
+            Some(4 * rank + suit)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the combo index of two distinct cards in any order (see `preflop_equity::COMBO_CARDS`).
+pub fn combo_index(card1: usize, card2: usize) -> usize {
+    let (lo, hi) = (card1.min(card2), card1.max(card2));
+    (103 - lo) * lo / 2 + (hi - lo - 1)
+}
+
+/// Returns the hand like `AhKd`, the higher card first.
+pub fn hand_str(hand: [usize; 2]) -> String {
+    let (lo, hi) = (hand[0].min(hand[1]), hand[0].max(hand[1]));
+    format!("{}{}", card_str(hi), card_str(lo))
+}
+
+/// Returns the hand class like `AKo`, `AKs` or `AA`.
+pub fn class_str(hand: [usize; 2]) -> String {
+    let (lo, hi) = (hand[0].min(hand[1]), hand[0].max(hand[1]));
+    let ranks = format!("{}{}", RANKS[hi / 4], RANKS[lo / 4]);
+    match (lo / 4 == hi / 4, lo % 4 == hi % 4) {
+        (true, _) => ranks,
+        (false, true) => ranks + "s",
+        (false, false) => ranks + "o",
+    }
+}
+
+/// Deals `n` distinct cards from a shuffled deck, skipping `dead` cards.
+pub fn deal(rng: &mut Rng, n: usize, dead: &[usize]) -> Vec<usize> {
+    let mut deck = (0..52)
+        .filter(|card| !dead.contains(card))
+        .collect::<Vec<_>>();
+    for i in 0..n {
+        let j = i + rng.below(deck.len() - i);
+        deck.swap(i, j);
+    }
+    deck.truncate(n);
+    deck
+}
+
+/// Returns the winner of the showdown between the hands of SB and BB on `board` (`None` for a
+/// split pot).
+pub fn showdown_winner(hands: [[usize; 2]; 2], board: &[usize]) -> Option<usize> {
+    let strength = |hand: [usize; 2]| hand_strength(&[&hand[..], board].concat());
+    match strength(hands[0]).cmp(&strength(hands[1])) {
+        std::cmp::Ordering::Greater => Some(0),
+        std::cmp::Ordering::Less => Some(1),
+        std::cmp::Ordering::Equal => None,
+    }
+}
@@ -5,75 +5,118 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
 use std::sync::Mutex;
-
-/// Vector-scalar multiplication.
-#[inline]
-fn mul_scalar(vec: &mut Vec<f64>, scalar: f64) {
-    for el in vec {
-        *el *= scalar;
+use std::time::{Duration, Instant};
+
+/// Number of elements processed together by the vector kernels. The kernels below iterate over
+/// fixed-size chunks so that the compiler can emit SIMD instructions without bounds checks.
+const LANES: usize = 8;
+
+/// Default scale of the regrets seeded by a warm-start strategy (see `seed_node`), chosen
+/// empirically on neighbouring stack depths of the pre-flop game.
+pub const WARM_START_REGRET_SCALE: f64 = 3.0;
+
+/// Interval of iterations at which the exploitability is checked against the target.
+const TARGET_CHECK_INTERVAL: usize = 100;
+
+/// Applies `f` to each pair of elements of `lhs` and `rhs`.
+#[inline(always)]
+fn zip_apply(lhs: &mut [f64], rhs: &[f64], f: impl Fn(&mut f64, f64)) {
+    assert_eq!(lhs.len(), rhs.len());
+    let mut lhs_chunks = lhs.chunks_exact_mut(LANES);
+    let mut rhs_chunks = rhs.chunks_exact(LANES);
+    for (l, r) in (&mut lhs_chunks).zip(&mut rhs_chunks) {
+        for i in 0..LANES {
+            f(&mut l[i], r[i]);
+        }
+    }
+    let lhs_rem = lhs_chunks.into_remainder();
+    for (l, r) in lhs_rem.iter_mut().zip(rhs_chunks.remainder()) {
+        f(l, *r);
     }
 }
 
-/// Force each element to be non-negative.
-#[inline]
-fn nonneg_vector(vec: &mut Vec<f64>) {
-    for el in vec {
-        *el = el.max(0.0);
+/// Applies `f` to each triple of elements of `lhs`, `a` and `b`.
+#[inline(always)]
+fn zip3_apply(lhs: &mut [f64], a: &[f64], b: &[f64], f: impl Fn(&mut f64, f64, f64)) {
+    assert_eq!(lhs.len(), a.len());
+    assert_eq!(lhs.len(), b.len());
+    let mut lhs_chunks = lhs.chunks_exact_mut(LANES);
+    let mut a_chunks = a.chunks_exact(LANES);
+    let mut b_chunks = b.chunks_exact(LANES);
+    for ((l, a), b) in (&mut lhs_chunks).zip(&mut a_chunks).zip(&mut b_chunks) {
+        for i in 0..LANES {
+            f(&mut l[i], a[i], b[i]);
+        }
+    }
+    let lhs_rem = lhs_chunks.into_remainder();
+    for ((l, a), b) in lhs_rem
+        .iter_mut()
+        .zip(a_chunks.remainder())
+        .zip(b_chunks.remainder())
+    {
+        f(l, *a, *b);
     }
 }
 
 /// Element-wise vector addition.
 #[inline]
-fn add_vector(lhs: &mut Vec<f64>, rhs: &Vec<f64>) {
-    for (l, r) in lhs.iter_mut().zip(rhs) {
-        *l += r;
-    }
+fn add_vector(lhs: &mut [f64], rhs: &[f64]) {
+    zip_apply(lhs, rhs, |l, r| *l += r);
 }
 
-/// Element-wise vector subtraction.
+/// Element-wise vector division. When denominator is zero, `default` value is used.
 #[inline]
-fn sub_vector(lhs: &mut Vec<f64>, rhs: &Vec<f64>) {
-    for (l, r) in lhs.iter_mut().zip(rhs) {
-        *l -= r;
-    }
+fn div_vector(lhs: &mut [f64], rhs: &[f64], default: f64) {
+    zip_apply(lhs, rhs, |l, r| {
+        *l = if r == 0.0 { default } else { *l / r }
+    });
 }
 
-/// Element-wise vector multiplication.
+/// Element-wise max operation.
 #[inline]
-fn mul_vector(lhs: &mut Vec<f64>, rhs: &Vec<f64>) {
-    for (l, r) in lhs.iter_mut().zip(rhs) {
-        *l *= r;
-    }
+fn max_vector(lhs: &mut [f64], rhs: &[f64]) {
+    zip_apply(lhs, rhs, |l, r| *l = l.max(r));
 }
 
-/// Element-wise vector division. When denominator is zero, `default` value is used.
+/// Stores the element-wise product of `a` and `b` to `out`.
 #[inline]
-fn div_vector(lhs: &mut Vec<f64>, rhs: &Vec<f64>, default: f64) {
-    for (l, r) in lhs.iter_mut().zip(rhs) {
-        if *r == 0.0 {
-            *l = default;
-        } else {
-            *l /= r;
-        }
-    }
+fn mul_vector_to(out: &mut [f64], a: &[f64], b: &[f64]) {
+    zip3_apply(out, a, b, |o, a, b| *o = a * b);
 }
 
-/// Element-wise max operation.
+/// Returns the element-wise product of `a` and `b`.
 #[inline]
-fn max_vector(lhs: &mut Vec<f64>, rhs: &Vec<f64>) {
-    for (l, r) in lhs.iter_mut().zip(rhs) {
-        *l = l.max(*r);
-    }
+fn product_vector(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut ret = vec![0.0; a.len()];
+    mul_vector_to(&mut ret, a, b);
+    ret
+}
+
+/// Adds the element-wise product of `a` and `b` to `lhs`.
+#[inline]
+fn add_mul_vector(lhs: &mut [f64], a: &[f64], b: &[f64]) {
+    zip3_apply(lhs, a, b, |l, a, b| *l += a * b);
 }
 
 /// Computes inner product.
 #[inline]
-fn dot(lhs: &Vec<f64>, rhs: &Vec<f64>) -> f64 {
-    let mut ret = 0.0;
-    for (l, r) in lhs.iter().zip(rhs) {
-        ret += l * r;
+fn dot(lhs: &[f64], rhs: &[f64]) -> f64 {
+    assert_eq!(lhs.len(), rhs.len());
+    let mut acc = [0.0; LANES];
+    let lhs_chunks = lhs.chunks_exact(LANES);
+    let rhs_chunks = rhs.chunks_exact(LANES);
+    let rem = lhs_chunks
+        .remainder()
+        .iter()
+        .zip(rhs_chunks.remainder())
+        .map(|(l, r)| l * r)
+        .sum::<f64>();
+    for (l, r) in lhs_chunks.zip(rhs_chunks) {
+        for i in 0..LANES {
+            acc[i] += l[i] * r[i];
+        }
     }
-    ret
+    acc.iter().sum::<f64>() + rem
 }
 
 /// Builds default tree.
@@ -111,14 +154,168 @@ fn build_tree_mt(node: &impl GameNode, tree: &mut HashMap<PublicInfoSet, Mutex<V
     }
 }
 
+/// Converts a tree built by `build_tree` for multi-threaded training.
+fn into_mutex_tree(
+    tree: HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+) -> HashMap<PublicInfoSet, Mutex<Vec<Vec<f64>>>> {
+    tree.into_iter()
+        .map(|(key, value)| (key, Mutex::new(value)))
+        .collect()
+}
+
+/// Seeds the cumulative regrets and sigmas with `warm_start`, a strategy, the number of iterations
+/// it counts as in the average strategy and the scale of the regrets (see `seed_node`), so that
+/// the first iteration plays it. The average strategy is seeded only when the number is positive:
+/// since CFR+ weights later iterations more, a seed from a different game mostly helps by starting
+/// the current strategy close to the solution, whereas resuming the same game benefits from the
+/// seeded average.
+#[allow(clippy::type_complexity)]
+fn seed_tree(
+    root: &impl GameNode,
+    warm_start: Option<(&HashMap<PublicInfoSet, Vec<Vec<f64>>>, usize, f64)>,
+    cum_cfr: &mut HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+    cum_sgm: &mut HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+) {
+    if let Some((seed, weight, regret_scale)) = warm_start {
+        // sum of the CFR+ weights of the first `weight` iterations (see `update_cumulative`)
+        let avg_weight = (weight * weight.saturating_sub(1) / 2) as f64;
+        let ones = vec![1.0; root.private_info_set_len()];
+        for player in 0..2 {
+            seed_node(
+                root,
+                player,
+                &ones,
+                &ones,
+                (seed, avg_weight, regret_scale),
+                cum_cfr,
+                cum_sgm,
+            );
+        }
+    }
+}
+
+/// Seeds the information sets of `player` below `node` (see `seed_tree`). Information sets missing
+/// in the seed or of a different shape are left untouched, i.e., start from the uniform strategy.
+///
+/// The regrets are set proportional to the seed so that regret matching reproduces it. Their
+/// scale is `regret_scale` times the largest difference of counterfactual values
+/// between actions, so that clearly bad actions have to build up regrets before being played.
+/// The cumulative sigmas are the seed weighted by `player`'s reach probability and `avg_weight`.
+/// Returns: counterfactual value
+fn seed_node(
+    node: &impl GameNode,
+    player: usize,
+    pi: &[f64],
+    pmi: &[f64],
+    (seed, avg_weight, regret_scale): (&HashMap<PublicInfoSet, Vec<Vec<f64>>>, f64, f64),
+    cum_cfr: &mut HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+    cum_sgm: &mut HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+) -> Vec<f64> {
+    if node.is_terminal_node() {
+        return node.evaluate(player, pmi);
+    }
+
+    let public_info_set = node.public_info_set();
+    let seeded = match seed.get(public_info_set) {
+        Some(strategy)
+            if strategy.len() == node.num_actions()
+                && strategy[0].len() == node.private_info_set_len() =>
+        {
+            Some(strategy)
+        }
+        _ => None,
+    };
+    let sigma = match seeded {
+        Some(strategy) => strategy.clone(),
+        None => regret_matching(&cum_cfr[public_info_set]),
+    };
+
+    let mut cfvalue = vec![0.0; node.private_info_set_len()];
+    if node.current_player() == player {
+        let mut cfvalue_action = Vec::with_capacity(node.num_actions());
+        for action in node.actions() {
+            let pi = product_vector(pi, &sigma[action]);
+            let seed = (seed, avg_weight, regret_scale);
+            let tmp = seed_node(&node.play(action), player, &pi, pmi, seed, cum_cfr, cum_sgm);
+            add_mul_vector(&mut cfvalue, &tmp, &sigma[action]);
+            cfvalue_action.push(tmp);
+        }
+
+        if seeded.is_some() {
+            let mut scale = vec![0.0; node.private_info_set_len()];
+            for tmp in &cfvalue_action {
+                zip3_apply(&mut scale, tmp, &cfvalue, |s, v, w| {
+                    *s = s.max(regret_scale * (v - w).abs())
+                });
+            }
+            let cum_cfr = cum_cfr.get_mut(public_info_set).unwrap();
+            let cum_sgm = cum_sgm.get_mut(public_info_set).unwrap();
+            for action in node.actions() {
+                mul_vector_to(&mut cum_cfr[action], &scale, &sigma[action]);
+                if avg_weight > 0.0 {
+                    zip3_apply(&mut cum_sgm[action], pi, &sigma[action], |s, p, q| {
+                        *s = p * avg_weight * q
+                    });
+                }
+            }
+        }
+    } else {
+        for action in node.actions() {
+            let pmi = product_vector(pmi, &sigma[action]);
+            let seed = (seed, avg_weight, regret_scale);
+            let tmp = seed_node(&node.play(action), player, pi, &pmi, seed, cum_cfr, cum_sgm);
+            add_vector(&mut cfvalue, &tmp);
+        }
+    }
+
+    cfvalue
+}
+
+/// Counts the tree built by `build_tree_mt` for each depth (number of actions played).
+/// Returns: [(public nodes, terminal nodes, info sets, estimated bytes of regrets and sigmas)]
+pub fn tree_stats(root: &impl GameNode) -> Vec<(usize, usize, usize, usize)> {
+    fn walk(node: &impl GameNode, stats: &mut Vec<(usize, usize, usize, usize)>) {
+        let depth = node.public_info_set().len();
+        if stats.len() <= depth {
+            stats.resize(depth + 1, (0, 0, 0, 0));
+        }
+
+        let entry = &mut stats[depth];
+        entry.0 += 1;
+        if node.is_terminal_node() {
+            entry.1 += 1;
+            return;
+        }
+
+        let num_actions = node.num_actions();
+        let private_info_set_len = node.private_info_set_len();
+        entry.2 += private_info_set_len;
+
+        // key, mutex, outer vector and `num_actions` inner vectors, for both regrets and sigmas
+        let vec_size = std::mem::size_of::<Vec<f64>>();
+        let table = std::mem::size_of::<(PublicInfoSet, Mutex<Vec<Vec<f64>>>)>()
+            + depth
+            + num_actions * (vec_size + private_info_set_len * std::mem::size_of::<f64>());
+        entry.3 += 2 * table;
+
+        for action in node.actions() {
+            walk(&node.play(action), stats);
+        }
+    }
+
+    let mut ret = Vec::new();
+    walk(root, &mut ret);
+    ret
+}
+
 /// Performs counterfactual regret minimization.
 /// Returns: counterfactual value
 fn cfr(
     node: &impl GameNode,
     iter: usize,
     player: usize,
-    pi: &Vec<f64>,
-    pmi: &Vec<f64>,
+    pi: &[f64],
+    pmi: &[f64],
     cum_cfr: &mut HashMap<PublicInfoSet, Vec<Vec<f64>>>,
     cum_sgm: &mut HashMap<PublicInfoSet, Vec<Vec<f64>>>,
 ) -> Vec<f64> {
@@ -130,6 +327,9 @@ fn cfr(
     // initialize counterfactual value
     let mut cfvalue = vec![0.0; node.private_info_set_len()];
 
+    // reach probabilities passed to children (reused across actions)
+    let mut scratch = vec![0.0; node.private_info_set_len()];
+
     // get current public information set
     let public_info_set = node.public_info_set();
 
@@ -140,36 +340,44 @@ fn cfr(
         let mut cfvalue_action = Vec::with_capacity(node.num_actions());
 
         for action in node.actions() {
-            let mut pi = pi.clone();
-            mul_vector(&mut pi, &sigma[action]);
-            let mut tmp = cfr(&node.play(action), iter, player, &pi, pmi, cum_cfr, cum_sgm);
-            cfvalue_action.push(tmp.clone());
-            mul_vector(&mut tmp, &sigma[action]);
-            add_vector(&mut cfvalue, &tmp);
+            mul_vector_to(&mut scratch, pi, &sigma[action]);
+            let tmp = cfr(
+                &node.play(action),
+                iter,
+                player,
+                &scratch,
+                pmi,
+                cum_cfr,
+                cum_sgm,
+            );
+            add_mul_vector(&mut cfvalue, &tmp, &sigma[action]);
+            cfvalue_action.push(tmp);
         }
 
         // update cumulative regrets and sigmas
-        for action in node.actions() {
-            let r = &mut cum_cfr.get_mut(public_info_set).unwrap()[action];
-            let s = &mut cum_sgm.get_mut(public_info_set).unwrap()[action];
-            let mut pi = pi.clone();
-            add_vector(r, &cfvalue_action[action]);
-            sub_vector(r, &cfvalue);
-
-            // Regret-matching+
-            nonneg_vector(r);
-
-            // CFR+
-            mul_scalar(&mut pi, iter as f64);
-
-            mul_vector(&mut pi, &sigma[action]);
-            add_vector(s, &pi);
-        }
+        let cum_cfr = cum_cfr.get_mut(public_info_set).unwrap();
+        let cum_sgm = cum_sgm.get_mut(public_info_set).unwrap();
+        update_cumulative(
+            iter,
+            pi,
+            &sigma,
+            &cfvalue,
+            &cfvalue_action,
+            cum_cfr,
+            cum_sgm,
+        );
     } else {
         for action in node.actions() {
-            let mut pmi = pmi.clone();
-            mul_vector(&mut pmi, &sigma[action]);
-            let tmp = cfr(&node.play(action), iter, player, pi, &pmi, cum_cfr, cum_sgm);
+            mul_vector_to(&mut scratch, pmi, &sigma[action]);
+            let tmp = cfr(
+                &node.play(action),
+                iter,
+                player,
+                pi,
+                &scratch,
+                cum_cfr,
+                cum_sgm,
+            );
             add_vector(&mut cfvalue, &tmp);
         }
     }
@@ -177,14 +385,39 @@ fn cfr(
     cfvalue
 }
 
+/// Updates cumulative regrets (Regret-matching+) and cumulative sigmas (CFR+ weighting).
+#[inline]
+fn update_cumulative(
+    iter: usize,
+    pi: &[f64],
+    sigma: &[Vec<f64>],
+    cfvalue: &[f64],
+    cfvalue_action: &[Vec<f64>],
+    cum_cfr: &mut [Vec<f64>],
+    cum_sgm: &mut [Vec<f64>],
+) {
+    let weight = iter as f64;
+    for action in 0..sigma.len() {
+        zip3_apply(
+            &mut cum_cfr[action],
+            &cfvalue_action[action],
+            cfvalue,
+            |r, v, w| *r = (*r + v - w).max(0.0),
+        );
+        zip3_apply(&mut cum_sgm[action], pi, &sigma[action], |s, p, q| {
+            *s += p * weight * q
+        });
+    }
+}
+
 /// Performs counterfactual regret minimization (multi-threaded version).
 /// Returns: counterfactual value
 fn cfr_mt(
     node: &impl GameNode,
     iter: usize,
     player: usize,
-    pi: &Vec<f64>,
-    pmi: &Vec<f64>,
+    pi: &[f64],
+    pmi: &[f64],
     cum_cfr: &HashMap<PublicInfoSet, Mutex<Vec<Vec<f64>>>>,
     cum_sgm: &HashMap<PublicInfoSet, Mutex<Vec<Vec<f64>>>>,
 ) -> Vec<f64> {
@@ -199,104 +432,108 @@ fn cfr_mt(
     // compute current sigma
     let sigma = regret_matching(&cum_cfr[public_info_set].lock().unwrap());
 
-    let cfvalue;
-    if node.current_player() == player {
-        let mut cfvalue_action = Vec::with_capacity(node.num_actions());
-        for _ in node.actions() {
-            cfvalue_action.push(Mutex::new(Vec::new()));
-        }
+    // reach probabilities passed to children (one buffer per rayon task, reused across actions)
+    let len = node.private_info_set_len();
+    let init_scratch = || vec![0.0; len];
 
-        cfvalue = node
+    let mut cfvalue = vec![0.0; len];
+    if node.current_player() == player {
+        let cfvalue_action = node
             .actions()
             .into_par_iter()
-            .map(|action| {
-                let mut pi = pi.clone();
-                mul_vector(&mut pi, &sigma[action]);
-                let mut tmp = cfr_mt(&node.play(action), iter, player, &pi, pmi, cum_cfr, cum_sgm);
-                *cfvalue_action[action].lock().unwrap() = tmp.clone();
-                mul_vector(&mut tmp, &sigma[action]);
-                tmp
+            .map_init(init_scratch, |scratch, action| {
+                mul_vector_to(scratch, pi, &sigma[action]);
+                cfr_mt(
+                    &node.play(action),
+                    iter,
+                    player,
+                    scratch,
+                    pmi,
+                    cum_cfr,
+                    cum_sgm,
+                )
             })
-            .reduce(
-                || vec![0.0; node.private_info_set_len()],
-                |mut v, w| {
-                    add_vector(&mut v, &w);
-                    v
-                },
-            );
+            .collect::<Vec<_>>();
+
+        for action in node.actions() {
+            add_mul_vector(&mut cfvalue, &cfvalue_action[action], &sigma[action]);
+        }
 
         // update cumulative regrets and sigmas
         let mut cum_cfr = cum_cfr[public_info_set].lock().unwrap();
         let mut cum_sgm = cum_sgm[public_info_set].lock().unwrap();
-        for action in node.actions() {
-            let r = &mut cum_cfr[action];
-            let mut pi = pi.clone();
-            add_vector(r, &cfvalue_action[action].lock().unwrap());
-            sub_vector(r, &cfvalue);
-
-            // Regret-matching+
-            nonneg_vector(r);
-
-            // CFR+
-            mul_scalar(&mut pi, iter as f64);
-
-            mul_vector(&mut pi, &sigma[action]);
-            add_vector(&mut cum_sgm[action], &pi);
-        }
+        update_cumulative(
+            iter,
+            pi,
+            &sigma,
+            &cfvalue,
+            &cfvalue_action,
+            &mut cum_cfr,
+            &mut cum_sgm,
+        );
     } else {
-        cfvalue = node
+        let cfvalue_action = node
             .actions()
             .into_par_iter()
-            .map(|action| {
-                let mut pmi = pmi.clone();
-                mul_vector(&mut pmi, &sigma[action]);
-                cfr_mt(&node.play(action), iter, player, pi, &pmi, cum_cfr, cum_sgm)
+            .map_init(init_scratch, |scratch, action| {
+                mul_vector_to(scratch, pmi, &sigma[action]);
+                cfr_mt(
+                    &node.play(action),
+                    iter,
+                    player,
+                    pi,
+                    scratch,
+                    cum_cfr,
+                    cum_sgm,
+                )
             })
-            .reduce(
-                || vec![0.0; node.private_info_set_len()],
-                |mut v, w| {
-                    add_vector(&mut v, &w);
-                    v
-                },
-            );
+            .collect::<Vec<_>>();
+
+        for tmp in &cfvalue_action {
+            add_vector(&mut cfvalue, tmp);
+        }
     }
 
     cfvalue
 }
 
 /// Performs regret matching.
-fn regret_matching(cum_cfr: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+fn regret_matching(cum_cfr: &[Vec<f64>]) -> Vec<Vec<f64>> {
     let num_actions = cum_cfr.len();
     let private_info_set_len = cum_cfr[0].len();
 
+    let mut result = cum_cfr
+        .iter()
+        .map(|cum_cfr_action| {
+            cum_cfr_action
+                .iter()
+                .map(|r| r.max(0.0))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
     let mut denom = vec![0.0; private_info_set_len];
-    for cum_cfr_action in cum_cfr {
-        let mut tmp = cum_cfr_action.clone();
-        nonneg_vector(&mut tmp);
-        add_vector(&mut denom, &tmp);
+    for tmp in &result {
+        add_vector(&mut denom, tmp);
     }
 
-    let mut result = Vec::with_capacity(num_actions);
-    for cum_cfr_action in cum_cfr {
-        let mut tmp = cum_cfr_action.clone();
-        nonneg_vector(&mut tmp);
-        div_vector(&mut tmp, &denom, 1.0 / num_actions as f64);
-        result.push(tmp);
+    for tmp in &mut result {
+        div_vector(tmp, &denom, 1.0 / num_actions as f64);
     }
 
     result
 }
 
 /// Computes `player`'s EV.
-fn compute_ev(
+pub fn compute_ev(
     node: &impl GameNode,
     player: usize,
-    pi: &Vec<f64>,
-    pmi: &Vec<f64>,
+    pi: &[f64],
+    pmi: &[f64],
     sigma: &HashMap<PublicInfoSet, Vec<Vec<f64>>>,
 ) -> f64 {
     if node.is_terminal_node() {
-        return dot(&node.evaluate(player, pmi), &pi);
+        return dot(&node.evaluate(player, pmi), pi);
     }
 
     let strategy = &sigma[node.public_info_set()];
@@ -305,8 +542,7 @@ fn compute_ev(
         node.actions()
             .into_par_iter()
             .map(|action| {
-                let mut pi = pi.clone();
-                mul_vector(&mut pi, &strategy[action]);
+                let pi = product_vector(pi, &strategy[action]);
                 compute_ev(&node.play(action), player, &pi, pmi, sigma)
             })
             .sum::<f64>()
@@ -314,8 +550,7 @@ fn compute_ev(
         node.actions()
             .into_par_iter()
             .map(|action| {
-                let mut pmi = pmi.clone();
-                mul_vector(&mut pmi, &strategy[action]);
+                let pmi = product_vector(pmi, &strategy[action]);
                 compute_ev(&node.play(action), player, pi, &pmi, sigma)
             })
             .sum::<f64>()
@@ -326,16 +561,15 @@ fn compute_ev(
 pub fn compute_ev_detail(
     node: &impl GameNode,
     player: usize,
-    pi: &Vec<f64>,
-    pmi: &Vec<f64>,
+    pi: &[f64],
+    pmi: &[f64],
     sigma: &HashMap<PublicInfoSet, Vec<Vec<f64>>>,
     ev: &Mutex<HashMap<PublicInfoSet, Vec<f64>>>,
 ) -> Vec<f64> {
     let public_info_set = node.public_info_set();
 
     if node.is_terminal_node() {
-        let mut pi = pi.clone();
-        mul_vector(&mut pi, &node.evaluate(player, pmi));
+        let pi = product_vector(pi, &node.evaluate(player, pmi));
         ev.lock()
             .unwrap()
             .insert(public_info_set.clone(), pi.clone());
@@ -349,12 +583,10 @@ pub fn compute_ev_detail(
         .into_par_iter()
         .map(|action| {
             if node.current_player() == player {
-                let mut pi = pi.clone();
-                mul_vector(&mut pi, &strategy[action]);
+                let pi = product_vector(pi, &strategy[action]);
                 compute_ev_detail(&node.play(action), player, &pi, pmi, sigma, ev)
             } else {
-                let mut pmi = pmi.clone();
-                mul_vector(&mut pmi, &strategy[action]);
+                let pmi = product_vector(pmi, &strategy[action]);
                 compute_ev_detail(&node.play(action), player, pi, &pmi, sigma, ev)
             }
         })
@@ -373,11 +605,66 @@ pub fn compute_ev_detail(
     result
 }
 
+/// Computes `player`'s counterfactual values weighted by `pi`, i.e., `compute_ev_detail` without
+/// storing the values of each node.
+fn compute_values(
+    node: &impl GameNode,
+    player: usize,
+    pi: &[f64],
+    pmi: &[f64],
+    sigma: &HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+) -> Vec<f64> {
+    if node.is_terminal_node() {
+        return product_vector(pi, &node.evaluate(player, pmi));
+    }
+
+    let strategy = &sigma[node.public_info_set()];
+
+    node.actions()
+        .into_par_iter()
+        .map(|action| {
+            if node.current_player() == player {
+                let pi = product_vector(pi, &strategy[action]);
+                compute_values(&node.play(action), player, &pi, pmi, sigma)
+            } else {
+                let pmi = product_vector(pmi, &strategy[action]);
+                compute_values(&node.play(action), player, pi, &pmi, sigma)
+            }
+        })
+        .reduce(
+            || vec![0.0; node.private_info_set_len()],
+            |mut v, w| {
+                add_vector(&mut v, &w);
+                v
+            },
+        )
+}
+
+/// Computes `player`'s counterfactual values of each action at the node reached by `key` from
+/// `root`, where `pmi` is the opponent's reach probabilities of the node. The values are defined
+/// even for actions `sigma` never takes.
+pub fn compute_action_values(
+    root: &impl GameNode,
+    player: usize,
+    key: &[u8],
+    pmi: &[f64],
+    sigma: &HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+) -> Vec<Vec<f64>> {
+    let mut node = root.clone();
+    for &action in key {
+        node = node.play(action as usize);
+    }
+    let ones = vec![1.0; node.private_info_set_len()];
+    node.actions()
+        .map(|action| compute_values(&node.play(action), player, &ones, pmi, sigma))
+        .collect()
+}
+
 /// Computes best response.
 fn compute_best_response(
     node: &impl GameNode,
     player: usize,
-    pmi: &Vec<f64>,
+    pmi: &[f64],
     sigma: &HashMap<PublicInfoSet, Vec<Vec<f64>>>,
 ) -> Vec<f64> {
     if node.is_terminal_node() {
@@ -400,8 +687,7 @@ fn compute_best_response(
         node.actions()
             .into_par_iter()
             .map(|action| {
-                let mut pmi = pmi.clone();
-                mul_vector(&mut pmi, &strategy[action]);
+                let pmi = product_vector(pmi, &strategy[action]);
                 compute_best_response(&node.play(action), player, &pmi, sigma)
             })
             .reduce(
@@ -414,15 +700,22 @@ fn compute_best_response(
     }
 }
 
-/// Computes exploitability.
-fn compute_exploitability(
+/// Computes exploitability, i.e., the sum of each player's gain by deviating to the best response.
+/// The EVs are subtracted explicitly for non-zero-sum games (e.g., ICM), where they do not cancel.
+pub fn compute_exploitability(
     root: &impl GameNode,
     sigma: &HashMap<PublicInfoSet, Vec<Vec<f64>>>,
 ) -> f64 {
     let ones = vec![1.0; root.private_info_set_len()];
     let br0 = compute_best_response(root, 0, &ones, sigma);
     let br1 = compute_best_response(root, 1, &ones, sigma);
-    br0.iter().sum::<f64>() + br1.iter().sum::<f64>()
+    let br = br0.iter().sum::<f64>() + br1.iter().sum::<f64>();
+    if root.is_zero_sum() {
+        return br;
+    }
+    let ev0 = compute_ev(root, 0, &ones, &ones, sigma);
+    let ev1 = compute_ev(root, 1, &ones, &ones, sigma);
+    br - ev0 - ev1
 }
 
 /// Computes average strategy.
@@ -466,7 +759,7 @@ fn compute_average_strategy_mt(
 
         let mut denom = vec![0.0; private_info_set_len];
         for cum_sigma_action in value.iter() {
-            add_vector(&mut denom, &cum_sigma_action);
+            add_vector(&mut denom, cum_sigma_action);
         }
 
         let mut result = Vec::with_capacity(num_actions);
@@ -482,26 +775,53 @@ fn compute_average_strategy_mt(
     ret
 }
 
-/// Performs training.
-/// Returns: (obtained strategy, player-0's EV, exploitability)
+/// Performs training. Stops early when the exploitability (checked every
+/// `TARGET_CHECK_INTERVAL` iterations) falls below `target`, and starts from `warm_start` instead
+/// of the uniform strategy when given (see `seed_tree`). The seed's number of iterations is not
+/// included in the returned number of iterations.
+/// Returns: (obtained strategy, player-0's EV, exploitability, number of iterations)
+#[allow(clippy::type_complexity)]
 pub fn train(
     root: &impl GameNode,
     num_iter: usize,
+    target: Option<f64>,
+    warm_start: Option<(&HashMap<PublicInfoSet, Vec<Vec<f64>>>, usize, f64)>,
     show_progress: bool,
-) -> (HashMap<PublicInfoSet, Vec<Vec<f64>>>, f64, f64) {
+) -> (HashMap<PublicInfoSet, Vec<Vec<f64>>>, f64, f64, usize) {
     let ones = vec![1.0; root.private_info_set_len()];
     let mut cum_cfr = HashMap::new();
     let mut cum_sgm = HashMap::new();
     build_tree(root, &mut cum_cfr);
     build_tree(root, &mut cum_sgm);
+    seed_tree(root, warm_start, &mut cum_cfr, &mut cum_sgm);
+    let offset = warm_start.map_or(0, |(_, weight, _)| weight);
 
+    let mut num_done = num_iter;
     for iter in 0..num_iter {
         if show_progress {
             print!("\riteration: {} / {}", iter + 1, num_iter);
         }
         std::io::stdout().flush().unwrap();
         for player in 0..2 {
-            cfr(root, iter, player, &ones, &ones, &mut cum_cfr, &mut cum_sgm);
+            cfr(
+                root,
+                offset + iter,
+                player,
+                &ones,
+                &ones,
+                &mut cum_cfr,
+                &mut cum_sgm,
+            );
+        }
+
+        if let Some(target) = target {
+            if (iter + 1) % TARGET_CHECK_INTERVAL == 0 {
+                let avg_sigma = compute_average_strategy(&cum_sgm);
+                if compute_exploitability(root, &avg_sigma) <= target {
+                    num_done = iter + 1;
+                    break;
+                }
+            }
         }
     }
     if show_progress {
@@ -511,23 +831,33 @@ pub fn train(
     let avg_sigma = compute_average_strategy(&cum_sgm);
     let ev = compute_ev(root, 0, &ones, &ones, &avg_sigma);
     let exploitability = compute_exploitability(root, &avg_sigma);
-    (avg_sigma, ev, exploitability)
+    (avg_sigma, ev, exploitability, num_done)
 }
 
-/// Performs training (multi-threaded version).
-/// Returns: (obtained strategy, player-0's EV, exploitability)
-pub fn train_mt<T: serde::Serialize>(
+/// Performs training (multi-threaded version). `target` and `warm_start` are the same as `train`.
+/// When `save_file_opt` is given, the intermediate results are saved every 1000 iterations
+/// together with the metadata of the game.
+/// Returns: (obtained strategy, player-0's EV, exploitability, number of iterations)
+#[allow(clippy::type_complexity)]
+pub fn train_mt<T: serde::Serialize, M: serde::Serialize>(
     root: &impl GameNode,
     num_iter: usize,
+    target: Option<f64>,
+    warm_start: Option<(&HashMap<PublicInfoSet, Vec<Vec<f64>>>, usize, f64)>,
     show_progress: bool,
-    save_file_opt: Option<(impl Fn(usize) -> String, impl Fn(&Vec<Vec<f64>>) -> T)>,
-) -> (HashMap<PublicInfoSet, Vec<Vec<f64>>>, f64, f64) {
+    save_file_opt: Option<(impl Fn(usize) -> String, impl Fn(&[Vec<f64>]) -> T, M)>,
+) -> (HashMap<PublicInfoSet, Vec<Vec<f64>>>, f64, f64, usize) {
     let ones = vec![1.0; root.private_info_set_len()];
     let mut cum_cfr = HashMap::new();
     let mut cum_sgm = HashMap::new();
-    build_tree_mt(root, &mut cum_cfr);
-    build_tree_mt(root, &mut cum_sgm);
+    build_tree(root, &mut cum_cfr);
+    build_tree(root, &mut cum_sgm);
+    seed_tree(root, warm_start, &mut cum_cfr, &mut cum_sgm);
+    let offset = warm_start.map_or(0, |(_, weight, _)| weight);
+    let cum_cfr = into_mutex_tree(cum_cfr);
+    let cum_sgm = into_mutex_tree(cum_sgm);
 
+    let mut num_done = num_iter;
     for iter in 0..num_iter {
         if show_progress {
             print!("\riteration: {} / {}", iter + 1, num_iter);
@@ -535,16 +865,27 @@ pub fn train_mt<T: serde::Serialize>(
         std::io::stdout().flush().unwrap();
 
         for player in 0..2 {
-            cfr_mt(root, iter, player, &ones, &ones, &cum_cfr, &cum_sgm);
+            cfr_mt(
+                root,
+                offset + iter,
+                player,
+                &ones,
+                &ones,
+                &cum_cfr,
+                &cum_sgm,
+            );
         }
 
-        if (iter + 1) % 1000 == 0 {
+        let is_checkpoint = (iter + 1) % 1000 == 0;
+        if is_checkpoint || (target.is_some() && (iter + 1) % TARGET_CHECK_INTERVAL == 0) {
             let avg_sigma = compute_average_strategy_mt(&cum_sgm);
             let exploitability = compute_exploitability(root, &avg_sigma);
             print!(" (exploitability = {:+.3e}[bb])", exploitability);
             std::io::stdout().flush().unwrap();
 
-            if let Some((outpath_fn, convert_fn)) = &save_file_opt {
+            if let Some((outpath_fn, convert_fn, metadata)) =
+                save_file_opt.as_ref().filter(|_| is_checkpoint)
+            {
                 if iter >= 1000 {
                     let prevpath = outpath_fn(iter - 999);
                     let _ = std::fs::remove_file(prevpath);
@@ -555,10 +896,15 @@ pub fn train_mt<T: serde::Serialize>(
                     .map(|(key, value)| (key.clone(), convert_fn(value)))
                     .collect::<HashMap<_, _>>();
                 let ev = compute_ev(root, 0, &ones, &ones, &avg_sigma);
-                let encoded = serialize(&(converted, ev, exploitability)).unwrap();
+                let encoded = serialize(&(converted, ev, exploitability, metadata)).unwrap();
                 let mut outfile = File::create(&outpath).unwrap();
                 outfile.write_all(&encoded).unwrap();
             }
+
+            if target.is_some_and(|target| exploitability <= target) {
+                num_done = iter + 1;
+                break;
+            }
         }
     }
 
@@ -566,9 +912,9 @@ pub fn train_mt<T: serde::Serialize>(
         println!();
     }
 
-    if let Some((outpath_fn, _)) = &save_file_opt {
-        if num_iter >= 1000 {
-            let prevpath = outpath_fn(num_iter - num_iter % 1000);
+    if let Some((outpath_fn, _, _)) = &save_file_opt {
+        if num_done >= 1000 {
+            let prevpath = outpath_fn(num_done - num_done % 1000);
             let _ = std::fs::remove_file(prevpath);
         }
     }
@@ -576,5 +922,39 @@ pub fn train_mt<T: serde::Serialize>(
     let avg_sigma = compute_average_strategy_mt(&cum_sgm);
     let ev = compute_ev(root, 0, &ones, &ones, &avg_sigma);
     let exploitability = compute_exploitability(root, &avg_sigma);
-    (avg_sigma, ev, exploitability)
+    (avg_sigma, ev, exploitability, num_done)
+}
+
+/// Runs `num_iter` iterations of training and returns the elapsed time of each iteration.
+pub fn time_iterations(root: &impl GameNode, num_iter: usize, multi_thread: bool) -> Vec<Duration> {
+    let ones = vec![1.0; root.private_info_set_len()];
+    let mut ret = Vec::with_capacity(num_iter);
+
+    if multi_thread {
+        let mut cum_cfr = HashMap::new();
+        let mut cum_sgm = HashMap::new();
+        build_tree_mt(root, &mut cum_cfr);
+        build_tree_mt(root, &mut cum_sgm);
+        for iter in 0..num_iter {
+            let start = Instant::now();
+            for player in 0..2 {
+                cfr_mt(root, iter, player, &ones, &ones, &cum_cfr, &cum_sgm);
+            }
+            ret.push(start.elapsed());
+        }
+    } else {
+        let mut cum_cfr = HashMap::new();
+        let mut cum_sgm = HashMap::new();
+        build_tree(root, &mut cum_cfr);
+        build_tree(root, &mut cum_sgm);
+        for iter in 0..num_iter {
+            let start = Instant::now();
+            for player in 0..2 {
+                cfr(root, iter, player, &ones, &ones, &mut cum_cfr, &mut cum_sgm);
+            }
+            ret.push(start.elapsed());
+        }
+    }
+
+    ret
 }
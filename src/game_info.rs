@@ -0,0 +1,151 @@
+use crate::blinds::Blinds;
+use crate::game_node::*;
+use crate::limp::Limp;
+use crate::rake::Rake;
+use bincode::deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Result};
+
+/// Game of an output file, from which the viewer rebuilds the game tree to compute EVs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum GameSpec {
+    /// Pre-flop only hold'em (see `PreflopNode::with_stacks`)
+    Preflop {
+        stacks: [f64; 2],
+        blinds: Blinds,
+        rake: Rake,
+        limp: Limp,
+    },
+    /// Push/fold hold'em in chips (see `PushFoldNode::with_stacks`)
+    PushFold {
+        stacks: [f64; 2],
+        blinds: Blinds,
+        rake: Rake,
+    },
+    /// Push/fold hold'em with arbitrary payoffs, e.g., under ICM (see `PushFoldNode::with_payoffs`)
+    PushFoldPayoffs {
+        payoffs: [[f64; 2]; 4],
+    },
+    Kuhn,
+}
+
+/// Arrangement of private information sets in the viewer's grid.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HandLayout {
+    /// 13x13 grid of hold'em hand classes over the 1326 combos
+    Holdem,
+    /// One row of the given cards, each of which is dealt to at most one player (e.g., J, Q and K
+    /// in Kuhn poker)
+    Cards(Vec<String>),
+}
+
+/// Metadata stored in output files so that the viewer can show any game.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameInfo {
+    pub spec: GameSpec,
+    /// Description of non-default settings, e.g., "ante 0.1, rake 5%" (may be empty)
+    pub description: String,
+    pub player_names: [String; 2],
+    /// Unit of payoffs, e.g., "bb"
+    pub unit: String,
+    pub hand_layout: HandLayout,
+    /// Labels of the actions at each public information set
+    pub action_labels: HashMap<PublicInfoSet, Vec<String>>,
+}
+
+impl GameInfo {
+    /// Creates the metadata of the game rooted at `root`. The player names, the unit and the hand
+    /// layout are the defaults of `spec`.
+    pub fn new(spec: GameSpec, description: &str, root: &impl GameNode) -> Self {
+        let (player_names, unit, hand_layout) = match &spec {
+            GameSpec::Preflop { .. } | GameSpec::PushFold { .. } => {
+                (["SB", "BB"], "bb", HandLayout::Holdem)
+            }
+            GameSpec::PushFoldPayoffs { .. } => (["Pusher", "BB"], "$", HandLayout::Holdem),
+            GameSpec::Kuhn => {
+                let cards = ["J", "Q", "K"].iter().map(|s| s.to_string()).collect();
+                (["Player 1", "Player 2"], "chips", HandLayout::Cards(cards))
+            }
+        };
+        Self {
+            spec,
+            description: description.to_string(),
+            player_names: [player_names[0].to_string(), player_names[1].to_string()],
+            unit: unit.to_string(),
+            hand_layout,
+            action_labels: action_labels(root),
+        }
+    }
+}
+
+/// Reads the metadata stored in an output file (`*.bin`).
+pub fn load_info(path: &str) -> Result<GameInfo> {
+    let mut infile = File::open(path)?;
+    let mut buf = Vec::new();
+    infile.read_to_end(&mut buf)?;
+    // (summarized strategy, EV, exploitability, rake, metadata)
+    type OutputType = (
+        HashMap<PublicInfoSet, Vec<Vec<Vec<f64>>>>,
+        f64,
+        f64,
+        Rake,
+        GameInfo,
+    );
+    deserialize::<OutputType>(&buf)
+        .map(|output| output.4)
+        .map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("'{}' has no metadata (written by an older version?)", path),
+            )
+        })
+}
+
+/// Collects the labels of the actions at each public information set below `node`.
+pub fn action_labels(node: &impl GameNode) -> HashMap<PublicInfoSet, Vec<String>> {
+    fn walk(node: &impl GameNode, ret: &mut HashMap<PublicInfoSet, Vec<String>>) {
+        if node.is_terminal_node() {
+            return;
+        }
+        let labels = node.actions().map(|action| node.action_label(action));
+        ret.insert(node.public_info_set().clone(), labels.collect());
+        for action in node.actions() {
+            walk(&node.play(action), ret);
+        }
+    }
+
+    let mut ret = HashMap::new();
+    walk(node, &mut ret);
+    ret
+}
+
+/// Returns the token of an action in lines, e.g., `F`, `X`, `R2.5` or `A`.
+pub fn action_token(label: &str) -> String {
+    match label {
+        "Check" => "X".to_string(),
+        _ if label.starts_with("Bet ") => format!(
+            "R{}",
+            label.trim_start_matches("Bet ").trim_end_matches('x')
+        ),
+        _ => label.chars().take(1).collect(),
+    }
+}
+
+/// Returns whether `token` (e.g., `F`, `C`, `X`, `R2.5`, `3x`, `A`) denotes `label`.
+pub fn action_matches(token: &str, label: &str) -> bool {
+    let token = token.to_ascii_lowercase();
+    match label {
+        "Fold" => token == "f" || token == "fold",
+        "Call" => token == "c" || token == "call",
+        "Check" => ["x", "c", "k", "check"].contains(&token.as_str()),
+        "All-in" => ["a", "ai", "allin", "all-in", "shove", "jam"].contains(&token.as_str()),
+        _ if label.starts_with("Bet ") => {
+            let size = label.trim_start_matches("Bet ").trim_end_matches('x');
+            let token = token.trim_start_matches(['r', 'b']);
+            token.trim_end_matches('x') == size
+        }
+        _ => token == label.to_ascii_lowercase() || token == action_token(label).to_lowercase(),
+    }
+}
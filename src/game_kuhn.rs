@@ -31,6 +31,12 @@ impl GameNode for KuhnNode {
         0..self.num_actions()
     }
 
+    fn action_label(&self, action: usize) -> String {
+        // facing a bet, the actions are fold and call
+        let facing_bet = self.public_info_set.last() == Some(&1);
+        [["Check", "Bet"], ["Fold", "Call"]][facing_bet as usize][action].to_string()
+    }
+
     #[inline]
     fn play(&self, action: usize) -> Self {
         let mut ret = self.clone();
@@ -49,7 +55,8 @@ impl GameNode for KuhnNode {
     }
 
     #[inline]
-    fn evaluate(&self, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
+    #[allow(clippy::needless_range_loop)]
+    fn evaluate(&self, player: usize, pmi: &[f64]) -> Vec<f64> {
         let mut ret = Vec::with_capacity(self.private_info_set_len());
         for i in 0..self.private_info_set_len() {
             let mut cfvalue = 0.0;
@@ -13,6 +13,9 @@ pub trait GameNode: Clone + Sync {
     /// Returns a set of valid actions.
     fn actions(&self) -> std::ops::Range<usize>;
 
+    /// Returns the label of `action` shown to users (e.g., "Fold", "Bet 2.5x").
+    fn action_label(&self, action: usize) -> String;
+
     /// Plays `action` and returns a node after `action` played.
     fn play(&self, action: usize) -> Self;
 
@@ -23,5 +26,19 @@ pub trait GameNode: Clone + Sync {
     fn private_info_set_len(&self) -> usize;
 
     /// Computes player's counterfactual values according to `pmi`.
-    fn evaluate(&self, player: usize, pmi: &Vec<f64>) -> Vec<f64>;
+    fn evaluate(&self, player: usize, pmi: &[f64]) -> Vec<f64>;
+
+    /// Returns whether the payoffs of the players always sum to zero, which is not the case
+    /// under rake or ICM.
+    fn is_zero_sum(&self) -> bool {
+        true
+    }
+}
+
+/// Game nodes of heads-up hold'em, where private information sets are the 1326 combos and the
+/// hands reaching showdown are decided by dealt cards (e.g., when playing sampled hands).
+pub trait HoldemNode: GameNode {
+    /// Returns `player`'s payoff at the current terminal node, where `winner` wins the showdown
+    /// (`None` for a split pot; ignored when someone folded).
+    fn realized_payoff(&self, player: usize, winner: Option<usize>) -> f64;
 }
@@ -1,34 +1,50 @@
+use crate::blinds::Blinds;
 use crate::game_node::*;
-use bincode::deserialize;
-use once_cell::sync::Lazy;
-use std::fs::File;
-use std::io::Read;
-
-static EQUITY_TABLE: Lazy<Vec<u32>> = Lazy::new(|| {
-    let path = "static/heads_up_pre_flop_equity.bin";
-    let mut infile = File::open(path).expect(&format!("could not open '{}'", path));
-    let mut buf = Vec::new();
-    infile.read_to_end(&mut buf).unwrap();
-    deserialize::<Vec<u32>>(&buf).unwrap()
-});
+use crate::limp::Limp;
+use crate::preflop_equity::*;
+use crate::rake::Rake;
 
+/// Raise sizes as multiples of the current bet, except after a limp with custom sizes.
+const RAISE_MULTIPLES: [f64; 4] = [2.5, 3.0, 3.5, 4.0];
+
+/// Maximum number of actions: fold, call, the raise sizes and all-in.
+const MAX_ACTIONS: usize = RAISE_MULTIPLES.len() + 3;
+
+/// Kind of a pre-flop action.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PreflopAction {
+    Fold,
+    /// Check or call
+    Call,
+    /// Raise to the given multiple of the current bet
+    Raise(f64),
+    AllIn,
+}
+
+// Actions are fold (always 0), call (except the first action when limps are disabled), the raise
+// sizes below the effective stack and all-in (if any chips are behind), in this order; e.g.,
 // 0 => Fold, 1 => Call, 2 => 2.5x bet, 3 => 3x bet, 4 => 3.5x bet, 5 => 4x bet, 6 => All in
 #[derive(Clone, Debug)]
 pub struct PreflopNode {
     prev_bet: f64,
     cur_bet: f64,
+    /// Maximum bet (effective stack excluding antes)
     eff_stack: f64,
+    blinds: Blinds,
+    rake: Rake,
+    limp: Limp,
+    /// Whether the current player may check after a limp (the pot-relative sizes apply)
+    after_limp: bool,
+    /// Whether the last action ended the hand
+    closed: bool,
+    isomorphic: bool,
     public_info_set: PublicInfoSet,
 }
 
 impl GameNode for PreflopNode {
     #[inline]
     fn is_terminal_node(&self) -> bool {
-        match self.public_info_set.last() {
-            Some(&0) => true,
-            Some(&1) => self.public_info_set.len() >= 2,
-            _ => false,
-        }
+        self.closed
     }
 
     #[inline]
@@ -38,14 +54,7 @@ impl GameNode for PreflopNode {
 
     #[inline]
     fn num_actions(&self) -> usize {
-        let ratio = self.eff_stack / self.cur_bet;
-        let mut ret = 2;
-        ret += (ratio > 1.0) as usize;
-        ret += (ratio > 2.5) as usize;
-        ret += (ratio > 3.0) as usize;
-        ret += (ratio > 3.5) as usize;
-        ret += (ratio > 4.0) as usize;
-        ret
+        self.action_kinds().1
     }
 
     #[inline]
@@ -53,20 +62,40 @@ impl GameNode for PreflopNode {
         0..self.num_actions()
     }
 
+    fn action_label(&self, action: usize) -> String {
+        match self.action(action) {
+            PreflopAction::Fold => "Fold".to_string(),
+            PreflopAction::Call if self.prev_bet == self.cur_bet => "Check".to_string(),
+            PreflopAction::Call => "Call".to_string(),
+            PreflopAction::Raise(multiple) => {
+                format!("Bet {}x", (multiple * 100.0).round() / 100.0)
+            }
+            PreflopAction::AllIn => "All-in".to_string(),
+        }
+    }
+
     #[inline]
     fn play(&self, action: usize) -> Self {
         let mut ret = self.clone();
-        if action > 0 {
-            ret.prev_bet = ret.cur_bet;
-            ret.cur_bet *= match action {
-                2 => 2.5,
-                3 => 3.0,
-                4 => 3.5,
-                5 => 4.0,
-                6 => self.eff_stack,
-                _ => 1.0,
-            };
-            ret.cur_bet = ret.cur_bet.min(self.eff_stack);
+        match self.action(action) {
+            PreflopAction::Fold => ret.closed = true,
+            PreflopAction::Call => {
+                // SB's limp and BB's check after a limp (unless it ends the hand) keep it open
+                let reopens = !self.limp.check_ends && self.public_info_set.len() == 1;
+                ret.after_limp = self.public_info_set.is_empty() || (self.after_limp && reopens);
+                ret.closed = !ret.after_limp;
+                ret.prev_bet = ret.cur_bet;
+            }
+            PreflopAction::Raise(multiple) => {
+                ret.after_limp = false;
+                ret.prev_bet = ret.cur_bet;
+                ret.cur_bet = (ret.cur_bet * multiple).min(self.eff_stack);
+            }
+            PreflopAction::AllIn => {
+                ret.after_limp = false;
+                ret.prev_bet = ret.cur_bet;
+                ret.cur_bet = self.eff_stack;
+            }
         }
         ret.public_info_set.push(action as u8);
         ret
@@ -79,78 +108,479 @@ impl GameNode for PreflopNode {
 
     #[inline]
     fn private_info_set_len(&self) -> usize {
-        52 * 51 / 2
+        if self.isomorphic {
+            NUM_CLASSES
+        } else {
+            NUM_COMBOS
+        }
     }
 
     #[inline]
-    fn evaluate(&self, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
-        let prob = (2. * 2.) / (52. * 51. * 50. * 49.);
-        let total = 2. * (48. * 47. * 46. * 45. * 44.) / (5. * 4. * 3. * 2.);
+    fn evaluate(&self, player: usize, pmi: &[f64]) -> Vec<f64> {
+        let (mean, half_diff) = self.terminal_payoff(player);
+        evaluate_terminal(pmi, self.isomorphic, mean, half_diff)
+    }
 
-        // someone folded
-        if self.public_info_set.last() == Some(&0) {
-            let pmi_sum = pmi.iter().sum::<f64>();
-            let mut pmi_sum_ex = [0.0; 52];
-
-            let mut k = 0;
-            for i in 0..51 {
-                for j in (i + 1)..52 {
-                    pmi_sum_ex[i] += pmi[k];
-                    pmi_sum_ex[j] += pmi[k];
-                    k += 1;
-                }
-            }
+    #[inline]
+    fn is_zero_sum(&self) -> bool {
+        self.rake.is_zero()
+    }
+}
 
-            let payoff = [self.prev_bet, -self.prev_bet][player ^ self.current_player()];
+impl HoldemNode for PreflopNode {
+    fn realized_payoff(&self, player: usize, winner: Option<usize>) -> f64 {
+        let (mean, half_diff) = self.terminal_payoff(player);
+        match winner {
+            Some(winner) if winner == player => mean + half_diff,
+            Some(_) => mean - half_diff,
+            None => mean,
+        }
+    }
+}
 
-            let mut k = 0;
-            let mut ret = Vec::with_capacity(self.private_info_set_len());
-            for i in 0..51 {
-                for j in (i + 1)..52 {
-                    ret.push(payoff * prob * (pmi_sum - pmi_sum_ex[i] - pmi_sum_ex[j] + pmi[k]));
-                    k += 1;
-                }
-            }
+impl PreflopNode {
+    /// Creates a root node whose private information sets are the 1326 combos. This is the
+    /// default for callers that need combo-level strategies or values (use `new_isomorphic` to
+    /// solve faster over hand classes).
+    #[inline]
+    pub fn new(eff_stack: f64) -> Self {
+        Self::with_blinds(eff_stack, Blinds::default())
+    }
 
-            return ret;
+    /// Creates a root node under the given forced bets. `eff_stack` includes the blinds and antes.
+    #[inline]
+    pub fn with_blinds(eff_stack: f64, blinds: Blinds) -> Self {
+        Self::with_stacks([eff_stack, eff_stack], blinds)
+    }
+
+    /// Creates a root node where SB and BB have the given stacks (including the blinds and antes).
+    /// Bets are capped by the smaller stack excluding the dead money.
+    #[inline]
+    pub fn with_stacks(stacks: [f64; 2], blinds: Blinds) -> Self {
+        Self {
+            prev_bet: blinds.sb,
+            cur_bet: blinds.bb,
+            eff_stack: (stacks[0] - blinds.dead(0)).min(stacks[1] - blinds.dead(1)),
+            blinds,
+            rake: Rake::default(),
+            limp: Limp::default(),
+            after_limp: false,
+            closed: false,
+            isomorphic: false,
+            public_info_set: Vec::new(),
+        }
+    }
+
+    /// Takes the given rake from the winner's pot.
+    #[inline]
+    pub fn with_rake(self, rake: Rake) -> Self {
+        Self { rake, ..self }
+    }
+
+    /// Applies the given options of limping.
+    #[inline]
+    pub fn with_limp(self, limp: Limp) -> Self {
+        Self { limp, ..self }
+    }
+
+    /// Switches private information sets to the 169 suit-isomorphic hand classes.
+    #[inline]
+    pub fn isomorphic(self) -> Self {
+        Self {
+            isomorphic: true,
+            ..self
+        }
+    }
+
+    /// Creates a root node whose private information sets are the 169 suit-isomorphic hand
+    /// classes instead of the 1326 combos (see `preflop_equity::class_index`).
+    #[inline]
+    pub fn new_isomorphic(eff_stack: f64) -> Self {
+        Self::new(eff_stack).isomorphic()
+    }
+
+    /// Returns the kind of `action` of a node with `num_actions` actions under the default limp
+    /// options, where the actions only depend on their number: fold, call, the raises that stay
+    /// below the stack and all-in.
+    #[inline]
+    pub fn action_kind(action: usize, num_actions: usize) -> PreflopAction {
+        match action {
+            0 => PreflopAction::Fold,
+            1 => PreflopAction::Call,
+            _ if action == num_actions - 1 => PreflopAction::AllIn,
+            _ => PreflopAction::Raise(RAISE_MULTIPLES[action - 2]),
         }
+    }
+
+    /// Returns the current bet to be called (excluding antes).
+    #[inline]
+    pub fn bet(&self) -> f64 {
+        self.cur_bet
+    }
+
+    /// Returns the dead money in the pot, i.e., the antes.
+    #[inline]
+    pub fn dead_money(&self) -> f64 {
+        self.blinds.dead(0) + self.blinds.dead(1)
+    }
+
+    /// Returns the kind of `action` at the current node.
+    #[inline]
+    pub fn action(&self, action: usize) -> PreflopAction {
+        let (kinds, len) = self.action_kinds();
+        assert!(action < len, "invalid action {}", action);
+        kinds[action]
+    }
+
+    /// Returns the kinds of the available actions (the first elements of the array) and their
+    /// number.
+    #[inline]
+    fn action_kinds(&self) -> ([PreflopAction; MAX_ACTIONS], usize) {
+        let mut kinds = [PreflopAction::Fold; MAX_ACTIONS];
+        let mut len = 1;
+        let mut push = |kind| {
+            kinds[len] = kind;
+            len += 1;
+        };
 
-        let mut k = 0;
-        let mut ret = Vec::with_capacity(self.private_info_set_len());
-
-        for i in 0..51 {
-            for j in (i + 1)..52 {
-                let k_start = k;
-                let mut cfvalue = 0.0;
-                for m in 0..51 {
-                    for n in (m + 1)..52 {
-                        if i == m || i == n || j == m || j == n {
-                            k += 1;
-                            continue;
-                        }
-                        let eq = EQUITY_TABLE[k] as f64 / total;
-                        let eq_minus = 1.0 - eq;
-                        let ev = self.cur_bet * (eq - eq_minus);
-                        cfvalue += ev * pmi[k - k_start];
-                        k += 1;
+        if !self.public_info_set.is_empty() || self.limp.allowed {
+            push(PreflopAction::Call);
+        }
+
+        let ratio = self.eff_stack / self.cur_bet;
+        match self.limp.raise_sizes() {
+            // a raise of `size` times the pot after a limp
+            Some(sizes) if self.after_limp => {
+                let pot = 2.0 * self.cur_bet + self.dead_money();
+                for size in sizes {
+                    let multiple = 1.0 + size * pot / self.cur_bet;
+                    if ratio > multiple {
+                        push(PreflopAction::Raise(multiple));
+                    }
+                }
+            }
+            _ => {
+                for &multiple in &RAISE_MULTIPLES {
+                    if ratio > multiple {
+                        push(PreflopAction::Raise(multiple));
                     }
                 }
-                ret.push(cfvalue * prob);
             }
         }
 
-        ret
+        if ratio > 1.0 {
+            push(PreflopAction::AllIn);
+        }
+        (kinds, len)
     }
-}
 
-impl PreflopNode {
+    /// Returns the mean and the half difference of `player`'s payoffs when winning and losing the
+    /// showdown at the current terminal node (the half difference is zero when someone folded).
     #[inline]
-    pub fn new(eff_stack: f64) -> Self {
-        Self {
-            prev_bet: 0.5,
-            cur_bet: 1.0,
-            eff_stack,
-            public_info_set: Vec::new(),
+    fn terminal_payoff(&self, player: usize) -> (f64, f64) {
+        let dead = self.blinds.dead(0) + self.blinds.dead(1);
+
+        // someone folded
+        if self.public_info_set.last() == Some(&0) {
+            let folder = self.current_player() ^ 1;
+            let amount = self.prev_bet + self.blinds.dead(folder);
+            let payoff = if player == folder {
+                -amount
+            } else {
+                amount - self.rake.amount(2.0 * self.prev_bet + dead, false)
+            };
+            return (payoff, 0.0);
+        }
+
+        // the winner takes the opponent's bet and dead money (minus the rake)
+        let rake = self.rake.amount(2.0 * self.cur_bet + dead, true);
+        let own_dead = self.blinds.dead(player);
+        let opp_dead = self.blinds.dead(player ^ 1);
+        let mean = (opp_dead - own_dead - rake) / 2.0;
+        let half_diff = self.cur_bet + (opp_dead + own_dead - rake) / 2.0;
+        (mean, half_diff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfr;
+    use crate::limp::MAX_LIMP_RAISES;
+    use crate::preflop_equity::tests::*;
+
+    #[test]
+    fn classes_match_combos() {
+        use_fixture();
+        let (class_strategy, class_ev, _, _) =
+            cfr::train(&PreflopNode::new_isomorphic(3.0), 3, None, None, false);
+        let (combo_strategy, combo_ev, _, _) =
+            cfr::train(&PreflopNode::new(3.0), 3, None, None, false);
+        assert!(
+            (class_ev - combo_ev).abs() < 1e-9,
+            "{} != {}",
+            class_ev,
+            combo_ev
+        );
+        assert_eq!(class_strategy.len(), combo_strategy.len());
+        for (key, strategy) in &combo_strategy {
+            let expanded = expand_classes(&class_strategy[key]);
+            for (e, s) in expanded.iter().flatten().zip(strategy.iter().flatten()) {
+                assert!((e - s).abs() < 1e-9, "{:?}: {} != {}", key, e, s);
+            }
+        }
+    }
+
+    /// Antes of 0.1 each, a BB ante of 1 and an effective stack of 20.
+    fn root_with_antes() -> PreflopNode {
+        let blinds = Blinds {
+            ante: 0.1,
+            bb_ante: 1.0,
+            ..Blinds::default()
+        };
+        PreflopNode::with_blinds(20.0, blinds)
+    }
+
+    #[test]
+    fn fold_with_antes() {
+        use_fixture();
+        let pmi = reach(7, NUM_COMBOS);
+
+        // SB raises to 2.5 and BB folds: BB loses its blind and antes, and SB wins them
+        let node = root_with_antes().play(2).play(0);
+        assert!(node.is_terminal_node());
+        assert_close(&node.evaluate(0, &pmi), &evaluate_pairwise(&pmi, 2.1, 2.1));
+        assert_close(
+            &node.evaluate(1, &pmi),
+            &evaluate_pairwise(&pmi, -2.1, -2.1),
+        );
+    }
+
+    #[test]
+    fn showdown_with_antes() {
+        use_fixture();
+        let pmi = reach(8, NUM_COMBOS);
+
+        // SB shoves 18.9 (the stack minus the antes of BB) and BB calls: the winner takes the
+        // opponent's bet and antes
+        let node = root_with_antes().play(6).play(1);
+        assert!(node.is_terminal_node());
+        let (sb_win, sb_lose) = (18.9 + 1.1, -(18.9 + 0.1));
+        assert_close(
+            &node.evaluate(0, &pmi),
+            &evaluate_pairwise(&pmi, sb_win, sb_lose),
+        );
+        assert_close(
+            &node.evaluate(1, &pmi),
+            &evaluate_pairwise(&pmi, -sb_lose, -sb_win),
+        );
+    }
+
+    #[test]
+    fn showdown_with_unequal_stacks() {
+        use_fixture();
+        let pmi = reach(9, NUM_COMBOS);
+        let blinds = Blinds {
+            ante: 0.1,
+            bb_ante: 1.0,
+            ..Blinds::default()
+        };
+
+        // the shorter stack (excluding the antes) caps the all-in: 19.9 when SB has 20 and BB has
+        // 30, and 18.9 when SB has 30 and BB has 20
+        for &(stacks, all_in) in [([20.0, 30.0], 19.9), ([30.0, 20.0], 18.9)].iter() {
+            let node = PreflopNode::with_stacks(stacks, blinds).play(6).play(1);
+            assert!(node.is_terminal_node());
+            let (sb_win, sb_lose) = (all_in + 1.1, -(all_in + 0.1));
+            assert_close(
+                &node.evaluate(0, &pmi),
+                &evaluate_pairwise(&pmi, sb_win, sb_lose),
+            );
+            assert_close(
+                &node.evaluate(1, &pmi),
+                &evaluate_pairwise(&pmi, -sb_lose, -sb_win),
+            );
+        }
+    }
+
+    /// Antes of 0.1 each and a BB ante of 1, stacks of 20 (SB) and 30 (BB), and a rake of 5%
+    /// capped at 1.5.
+    fn root_with_rake(no_flop_no_drop: bool) -> PreflopNode {
+        let blinds = Blinds {
+            ante: 0.1,
+            bb_ante: 1.0,
+            ..Blinds::default()
+        };
+        let rake = Rake {
+            percent: 5.0,
+            cap: Some(1.5),
+            no_flop_no_drop,
+        };
+        PreflopNode::with_stacks([20.0, 30.0], blinds).with_rake(rake)
+    }
+
+    #[test]
+    fn fold_with_rake() {
+        use_fixture();
+        let pmi = reach(10, NUM_COMBOS);
+
+        // SB raises to 2.5 and BB folds: SB wins BB's blind and antes minus 5% of the pot of
+        // 1 + 1 + 1.2 (the uncalled part of the raise is returned), unless no flop no drop
+        for &(no_flop_no_drop, rake) in [(false, 0.05 * 3.2), (true, 0.0)].iter() {
+            let node = root_with_rake(no_flop_no_drop).play(2).play(0);
+            assert!(node.is_terminal_node());
+            let (sb, bb) = (2.1 - rake, -2.1);
+            assert_close(&node.evaluate(0, &pmi), &evaluate_pairwise(&pmi, sb, sb));
+            assert_close(&node.evaluate(1, &pmi), &evaluate_pairwise(&pmi, bb, bb));
+        }
+    }
+
+    #[test]
+    fn showdown_with_rake() {
+        use_fixture();
+        let pmi = reach(11, NUM_COMBOS);
+
+        // SB shoves 19.9 and BB calls: the winner takes the opponent's bet and antes minus the
+        // rake capped at 1.5
+        let root = root_with_rake(true);
+        assert!(!root.is_zero_sum());
+        let node = root.play(6).play(1);
+        assert!(node.is_terminal_node());
+        let (sb_win, sb_lose) = (19.9 + 1.1 - 1.5, -(19.9 + 0.1));
+        let (bb_win, bb_lose) = (19.9 + 0.1 - 1.5, -(19.9 + 1.1));
+        assert_close(
+            &node.evaluate(0, &pmi),
+            &evaluate_pairwise(&pmi, sb_win, sb_lose),
+        );
+        assert_close(
+            &node.evaluate(1, &pmi),
+            &evaluate_pairwise(&pmi, bb_win, bb_lose),
+        );
+    }
+
+    fn kinds(node: &PreflopNode) -> Vec<PreflopAction> {
+        node.actions().map(|action| node.action(action)).collect()
+    }
+
+    fn labels(node: &PreflopNode) -> Vec<String> {
+        node.actions()
+            .map(|action| node.action_label(action))
+            .collect()
+    }
+
+    fn limp_raises(sizes: &[f64], check_ends: bool) -> Limp {
+        let mut raises = [0.0; MAX_LIMP_RAISES];
+        raises[..sizes.len()].copy_from_slice(sizes);
+        Limp {
+            raises: Some((raises, sizes.len())),
+            check_ends,
+            ..Limp::default()
         }
     }
+
+    #[test]
+    fn no_limp() {
+        use PreflopAction::*;
+        let limp = Limp {
+            allowed: false,
+            ..Limp::default()
+        };
+        let root = PreflopNode::new(20.0).with_limp(limp);
+        assert_eq!(
+            kinds(&root),
+            [Fold, Raise(2.5), Raise(3.0), Raise(3.5), Raise(4.0), AllIn]
+        );
+        assert_eq!(
+            labels(&root),
+            ["Fold", "Bet 2.5x", "Bet 3x", "Bet 3.5x", "Bet 4x", "All-in"]
+        );
+
+        // BB may call a raise as usual
+        let node = root.play(1);
+        assert_eq!(node.bet(), 2.5);
+        assert_eq!(node.action(1), Call);
+        assert!(node.play(1).is_terminal_node());
+    }
+
+    #[test]
+    fn check_and_call_labels() {
+        let root = PreflopNode::new(20.0);
+        assert_eq!(root.action_label(1), "Call");
+        let limped = root.play(1);
+        assert!(!limped.is_terminal_node());
+        assert_eq!(limped.action_label(1), "Check");
+        assert_eq!(limped.play(2).action_label(1), "Call");
+    }
+
+    #[test]
+    fn limp_raise_sizes() {
+        use PreflopAction::*;
+
+        // after a limp, the pot is 1 + 1 + 1.2 (antes), so raises of 0.5 and 1 pot are raises to
+        // 1 + 0.5 * 3.2 = 2.6 and 1 + 3.2 = 4.2 times the bet of 1
+        let limp = limp_raises(&[0.5, 1.0], true);
+        let limped = root_with_antes().with_limp(limp).play(1);
+        assert_eq!(kinds(&limped), [Fold, Call, Raise(2.6), Raise(4.2), AllIn]);
+        assert_eq!(
+            labels(&limped),
+            ["Fold", "Check", "Bet 2.6x", "Bet 4.2x", "All-in"]
+        );
+        assert!((limped.play(2).bet() - 2.6).abs() < 1e-9);
+
+        // the usual sizes apply after a raise, and the pot-relative sizes must stay below the stack
+        assert_eq!(limped.play(2).action(2), Raise(2.5));
+        let short = PreflopNode::with_blinds(4.0, root_with_antes().blinds).with_limp(limp);
+        assert_eq!(kinds(&short.play(1)), [Fold, Call, Raise(2.6), AllIn]);
+
+        // no raise but all-in
+        let limp = limp_raises(&[], true);
+        assert_eq!(
+            kinds(&root_with_antes().with_limp(limp).play(1)),
+            [Fold, Call, AllIn]
+        );
+
+        // up to `MAX_LIMP_RAISES` sizes fill all the actions
+        let limp = limp_raises(&[0.25, 0.5, 0.75, 1.0], true);
+        let limped = PreflopNode::new(100.0).with_limp(limp).play(1);
+        assert_eq!(limped.num_actions(), MAX_ACTIONS);
+        assert_eq!(
+            kinds(&limped),
+            [
+                Fold,
+                Call,
+                Raise(1.5),
+                Raise(2.0),
+                Raise(2.5),
+                Raise(3.0),
+                AllIn
+            ]
+        );
+    }
+
+    #[test]
+    fn check_after_limp() {
+        use_fixture();
+        let pmi = reach(12, NUM_COMBOS);
+
+        // by default, BB's check ends the hand with a showdown of 1 each
+        let node = PreflopNode::new(20.0).play(1).play(1);
+        assert!(node.is_terminal_node());
+        assert_close(&node.evaluate(0, &pmi), &evaluate_pairwise(&pmi, 1.0, -1.0));
+
+        // otherwise, SB may check or bet again with the sizes after a limp
+        let limp = limp_raises(&[1.0], false);
+        let checked = PreflopNode::new(20.0).with_limp(limp).play(1).play(1);
+        assert!(!checked.is_terminal_node());
+        assert_eq!(checked.current_player(), 0);
+        assert_eq!(labels(&checked), ["Fold", "Check", "Bet 3x", "All-in"]);
+        let node = checked.play(1);
+        assert!(node.is_terminal_node());
+        assert_close(&node.evaluate(0, &pmi), &evaluate_pairwise(&pmi, 1.0, -1.0));
+
+        // BB may then call or raise SB's bet, but not check again
+        let bet = checked.play(2);
+        assert_eq!(bet.action_label(1), "Call");
+        assert!(bet.play(1).is_terminal_node());
+        assert_eq!(bet.action(2), PreflopAction::Raise(2.5));
+    }
 }
@@ -1,32 +1,26 @@
+use crate::blinds::Blinds;
 use crate::game_node::*;
-use bincode::deserialize;
-use once_cell::sync::Lazy;
-use rayon::prelude::*;
-use std::fs::File;
-use std::io::Read;
-
-static EQUITY_TABLE: Lazy<Vec<u32>> = Lazy::new(|| {
-    let path = "static/heads_up_pre_flop_equity.bin";
-    let mut infile = File::open(path).expect(&format!("could not open '{}'", path));
-    let mut buf = Vec::new();
-    infile.read_to_end(&mut buf).unwrap();
-    deserialize::<Vec<u32>>(&buf).unwrap()
-});
+use crate::preflop_equity::*;
+use crate::rake::Rake;
+
+/// Payoff of (SB, BB) for each outcome: [SB folds, BB folds, SB wins showdown, BB wins showdown].
+/// A tie at showdown is treated as winning and losing with probability 1/2 each.
+pub type Payoffs = [[f64; 2]; 4];
 
 #[derive(Clone, Debug)]
 pub struct PushFoldNode {
-    eff_stack: f64,
+    payoffs: Payoffs,
+    /// Pot size of each outcome, on which the rake is based
+    pots: [f64; 4],
+    rake: Rake,
+    isomorphic: bool,
     public_info_set: PublicInfoSet,
 }
 
 impl GameNode for PushFoldNode {
     #[inline]
     fn is_terminal_node(&self) -> bool {
-        match self.public_info_set.as_slice() {
-            [0] => true,
-            [_, _] => true,
-            _ => false,
-        }
+        matches!(self.public_info_set.as_slice(), [0] | [_, _])
     }
 
     #[inline]
@@ -44,6 +38,15 @@ impl GameNode for PushFoldNode {
         0..self.num_actions()
     }
 
+    fn action_label(&self, action: usize) -> String {
+        match (self.public_info_set.is_empty(), action) {
+            (_, 0) => "Fold",
+            (true, _) => "All-in",
+            (false, _) => "Call",
+        }
+        .to_string()
+    }
+
     #[inline]
     fn play(&self, action: usize) -> Self {
         let mut ret = self.clone();
@@ -58,81 +61,138 @@ impl GameNode for PushFoldNode {
 
     #[inline]
     fn private_info_set_len(&self) -> usize {
-        52 * 51 / 2
+        if self.isomorphic {
+            NUM_CLASSES
+        } else {
+            NUM_COMBOS
+        }
     }
 
     #[inline]
-    fn evaluate(&self, player: usize, pmi: &Vec<f64>) -> Vec<f64> {
-        let prob = (2. * 2.) / (52. * 51. * 50. * 49.);
-        let total = 2. * (48. * 47. * 46. * 45. * 44.) / (5. * 4. * 3. * 2.);
-
+    fn evaluate(&self, player: usize, pmi: &[f64]) -> Vec<f64> {
         // someone folded
         if self.public_info_set.last() == Some(&0) {
-            let pmi_sum = pmi.iter().sum::<f64>();
-            let mut pmi_sum_ex = [0.0; 52];
-
-            let mut k = 0;
-            for i in 0..51 {
-                for j in (i + 1)..52 {
-                    pmi_sum_ex[i] += pmi[k];
-                    pmi_sum_ex[j] += pmi[k];
-                    k += 1;
-                }
-            }
-
-            let payoff = match self.public_info_set.len() {
-                1 => [-0.5, 0.5][player],
-                _ => [1.0, -1.0][player],
-            };
-
-            let mut k = 0;
-            let mut ret = Vec::with_capacity(self.private_info_set_len());
-            for i in 0..51 {
-                for j in (i + 1)..52 {
-                    ret.push(payoff * prob * (pmi_sum - pmi_sum_ex[i] - pmi_sum_ex[j] + pmi[k]));
-                    k += 1;
-                }
-            }
-
-            return ret;
+            let payoff = self.payoff(self.public_info_set.len() - 1, player);
+            return evaluate_terminal(pmi, self.isomorphic, payoff, 0.0);
         }
 
-        (0usize..51)
-            .into_par_iter()
-            .map(|i| {
-                let mut k = ((103 - i) * i / 2) * self.private_info_set_len();
-                let mut ret = Vec::with_capacity(51 - i);
-                for j in (i + 1)..52 {
-                    let k_start = k;
-                    let mut cfvalue = 0.0;
-                    for m in 0..51 {
-                        for n in (m + 1)..52 {
-                            if i == m || i == n || j == m || j == n {
-                                k += 1;
-                                continue;
-                            }
-                            let eq = EQUITY_TABLE[k] as f64 / total;
-                            let eq_minus = 1.0 - eq;
-                            let ev = self.eff_stack * (eq - eq_minus);
-                            cfvalue += ev * pmi[k - k_start];
-                            k += 1;
-                        }
-                    }
-                    ret.push(cfvalue * prob);
-                }
-                ret
-            })
-            .flatten()
-            .collect::<Vec<f64>>()
+        let (mean, half_diff) = self.showdown_payoff(player);
+        evaluate_terminal(pmi, self.isomorphic, mean, half_diff)
+    }
+
+    #[inline]
+    fn is_zero_sum(&self) -> bool {
+        let zero_sum = |payoff: &[f64; 2]| payoff[0] + payoff[1] == 0.0;
+        self.rake.is_zero() && self.payoffs.iter().all(zero_sum)
+    }
+}
+
+impl HoldemNode for PushFoldNode {
+    fn realized_payoff(&self, player: usize, winner: Option<usize>) -> f64 {
+        if self.public_info_set.last() == Some(&0) {
+            return self.payoff(self.public_info_set.len() - 1, player);
+        }
+        match winner {
+            Some(winner) => self.payoff(2 + winner, player),
+            None => self.showdown_payoff(player).0,
+        }
     }
 }
 
 impl PushFoldNode {
     #[inline]
     pub fn new(eff_stack: f64) -> Self {
+        Self::with_blinds(eff_stack, Blinds::default())
+    }
+
+    /// Creates a root node with chip payoffs under the given forced bets. `eff_stack` includes
+    /// the blinds and antes.
+    #[inline]
+    pub fn with_blinds(eff_stack: f64, blinds: Blinds) -> Self {
+        Self::with_stacks([eff_stack, eff_stack], blinds)
+    }
+
+    /// Creates a root node with chip payoffs where SB and BB have the given stacks (including the
+    /// blinds and antes). At showdown, the loser loses the dead money and the effective all-in
+    /// bet, i.e., the smaller stack excluding the dead money.
+    #[inline]
+    pub fn with_stacks(stacks: [f64; 2], blinds: Blinds) -> Self {
+        let sb_fold = blinds.sb + blinds.dead(0);
+        let bb_fold = blinds.bb + blinds.dead(1);
+        let all_in = (stacks[0] - blinds.dead(0)).min(stacks[1] - blinds.dead(1));
+        let sb_showdown = all_in + blinds.dead(0);
+        let bb_showdown = all_in + blinds.dead(1);
+        let dead = blinds.dead(0) + blinds.dead(1);
+        Self {
+            pots: [
+                2.0 * blinds.sb + dead,
+                2.0 * blinds.bb + dead,
+                2.0 * all_in + dead,
+                2.0 * all_in + dead,
+            ],
+            ..Self::with_payoffs([
+                [-sb_fold, sb_fold],
+                [bb_fold, -bb_fold],
+                [bb_showdown, -bb_showdown],
+                [-sb_showdown, sb_showdown],
+            ])
+        }
+    }
+
+    /// Creates a root node with arbitrary payoffs (e.g., $EV under ICM), which need not be
+    /// zero-sum.
+    #[inline]
+    pub fn with_payoffs(payoffs: Payoffs) -> Self {
         Self {
-            eff_stack,
+            payoffs,
+            pots: [0.0; 4],
+            rake: Rake::default(),
+            isomorphic: false,
             public_info_set: Vec::new(),
         }
     }
+
+    /// Takes the given rake from the winner's pot. Rake only applies to chip payoffs.
+    #[inline]
+    pub fn with_rake(self, rake: Rake) -> Self {
+        Self { rake, ..self }
+    }
+
+    /// Switches private information sets to the 169 suit-isomorphic hand classes.
+    #[inline]
+    pub fn isomorphic(self) -> Self {
+        Self {
+            isomorphic: true,
+            ..self
+        }
+    }
+
+    /// Creates a root node whose private information sets are the 169 suit-isomorphic hand
+    /// classes instead of the 1326 combos (see `preflop_equity::class_index`).
+    #[inline]
+    pub fn new_isomorphic(eff_stack: f64) -> Self {
+        Self::new(eff_stack).isomorphic()
+    }
+
+    /// Returns the mean and the half difference of `player`'s payoffs when winning and losing the
+    /// showdown, so that the payoff is `mean + half_diff * (2 * equity - 1)`.
+    #[inline]
+    fn showdown_payoff(&self, player: usize) -> (f64, f64) {
+        let (win, lose) = match player {
+            0 => (self.payoff(2, 0), self.payoff(3, 0)),
+            _ => (self.payoff(3, 1), self.payoff(2, 1)),
+        };
+        ((win + lose) / 2.0, (win - lose) / 2.0)
+    }
+
+    /// Returns `player`'s payoff of `outcome` (see `Payoffs`), where the winner pays the rake.
+    #[inline]
+    fn payoff(&self, outcome: usize, player: usize) -> f64 {
+        let winner = [1, 0, 0, 1][outcome];
+        let mut ret = self.payoffs[outcome][player];
+        if player == winner {
+            ret -= self.rake.amount(self.pots[outcome], outcome >= 2);
+        }
+        ret
+    }
 }
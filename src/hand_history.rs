@@ -0,0 +1,298 @@
+use crate::cards::parse_card;
+use regex::Regex;
+
+/// Pre-flop action in a hand history. Amounts are in big blinds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HistoryAction {
+    Fold,
+    /// Check or call
+    Call,
+    /// Bet or raise to the given amount (excluding antes); the flag is set when it is all-in
+    Raise(f64, bool),
+}
+
+/// Heads-up hand parsed from a hand history. Player 0 is SB (the button) and player 1 is BB, and
+/// amounts are in big blinds.
+#[derive(Clone, Debug)]
+pub struct HandHistory {
+    pub id: String,
+    pub players: [String; 2],
+    /// Stacks at the start of the hand, including the blinds and antes
+    pub stacks: [f64; 2],
+    pub sb: f64,
+    pub antes: [f64; 2],
+    /// Hero's seat and hole cards, i.e., the player of the `Dealt to` line
+    pub hero: Option<(usize, [usize; 2])>,
+    /// Pre-flop actions with the acting player
+    pub actions: Vec<(usize, HistoryAction)>,
+}
+
+impl HistoryAction {
+    /// Returns the action in the history's terms, e.g., `Raise to 2.2bb`.
+    pub fn description(&self) -> String {
+        match *self {
+            HistoryAction::Fold => "Fold".to_string(),
+            HistoryAction::Call => "Check/Call".to_string(),
+            HistoryAction::Raise(amount, false) => format!("Raise to {}bb", round(amount)),
+            HistoryAction::Raise(amount, true) => format!("All-in {}bb", round(amount)),
+        }
+    }
+}
+
+/// Parses hand histories in the PokerStars plain-text format (also exported by many other sites
+/// and tracking tools). Returns the id of each hand with the parsed hand or the reason why it
+/// cannot be analyzed.
+pub fn parse_histories(text: &str) -> Vec<(String, Result<HandHistory, String>)> {
+    let header_re = Regex::new(r"^[A-Za-z' ]*(?:Hand|Game) #(\d+)").unwrap();
+    let mut hands = Vec::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    for line in text.lines() {
+        let line = line.trim_start_matches('\u{feff}').trim_end();
+        if let Some(cs) = header_re.captures(line) {
+            hands.extend(current.take());
+            current = Some((cs[1].to_string(), Vec::new()));
+        } else if let Some((_, lines)) = &mut current {
+            lines.push(line);
+        }
+    }
+    hands.extend(current);
+
+    hands
+        .into_iter()
+        .map(|(id, lines)| {
+            let hand = parse_hand(&id, &lines);
+            (id, hand)
+        })
+        .collect()
+}
+
+fn parse_hand(id: &str, lines: &[&str]) -> Result<HandHistory, String> {
+    let seat_re = Regex::new(r"^Seat \d+: (.+) \(([^ ]+) in chips").unwrap();
+    let dealt_re = Regex::new(r"^Dealt to (.+) \[(\w\w) (\w\w)\]").unwrap();
+
+    let mut seats = Vec::new();
+    for line in lines.iter().take_while(|line| !line.starts_with("*** ")) {
+        if let Some(cs) = seat_re.captures(line) {
+            let stack = parse_amount(&cs[2]).ok_or("invalid stack")?;
+            seats.push((cs[1].to_string(), stack));
+        }
+    }
+    if seats.len() != 2 {
+        return Err(format!("not heads-up ({} players)", seats.len()));
+    }
+
+    // blinds and antes are posted before the hole cards are dealt
+    let mut players = [None, None];
+    let mut blinds = [0.0; 2];
+    let mut antes = [0.0; 2];
+    let mut hero = None;
+    let mut index = lines
+        .iter()
+        .position(|line| line.starts_with("*** HOLE CARDS ***"))
+        .ok_or("no hole cards")?;
+    for line in &lines[..index] {
+        for (seat, (name, _)) in seats.iter().enumerate() {
+            let action = match line.strip_prefix(&format!("{}: ", name)) {
+                Some(action) => action,
+                None => continue,
+            };
+            // the amount follows the prefix, e.g., `posts small blind 5 and is all-in`
+            let amount = |prefix: &str| {
+                let rest = action.strip_prefix(prefix)?;
+                rest.split(' ').next().and_then(parse_amount)
+            };
+            if action.starts_with("posts small blind ") {
+                players[0] = Some(seat);
+                blinds[0] = amount("posts small blind ").ok_or("invalid small blind")?;
+            } else if action.starts_with("posts big blind ") {
+                players[1] = Some(seat);
+                blinds[1] = amount("posts big blind ").ok_or("invalid big blind")?;
+            } else if action.starts_with("posts the ante ") {
+                antes[seat] = amount("posts the ante ").ok_or("invalid ante")?;
+            }
+        }
+    }
+    let players = match players {
+        [Some(sb), Some(bb)] if sb != bb => [sb, bb],
+        _ => return Err("missing blinds".to_string()),
+    };
+    let bb = blinds[1];
+    let names = [seats[players[0]].0.clone(), seats[players[1]].0.clone()];
+    let stacks = [seats[players[0]].1 / bb, seats[players[1]].1 / bb];
+    let antes = [antes[players[0]] / bb, antes[players[1]] / bb];
+
+    let mut actions = Vec::new();
+    index += 1;
+    for line in &lines[index..] {
+        if line.starts_with("*** ") {
+            break;
+        }
+        if let Some(cs) = dealt_re.captures(line) {
+            let seat = names.iter().position(|name| name == &cs[1]);
+            let cards = [parse_card(&cs[2]), parse_card(&cs[3])];
+            if let (Some(seat), [Some(card1), Some(card2)]) = (seat, cards) {
+                hero = Some((seat, [card1, card2]));
+            }
+            continue;
+        }
+        for (player, name) in names.iter().enumerate() {
+            let action = match line.strip_prefix(&format!("{}: ", name)) {
+                Some(action) => action,
+                None => continue,
+            };
+            let all_in = action.ends_with("and is all-in");
+            let words = action.split(' ').collect::<Vec<_>>();
+            let action = match words.as_slice() {
+                ["folds", ..] => HistoryAction::Fold,
+                ["checks", ..] | ["calls", ..] => HistoryAction::Call,
+                ["raises", _, "to", amount, ..] | ["bets", amount, ..] => {
+                    let amount = parse_amount(amount).ok_or("invalid raise")? / bb;
+                    // players may be all-in without the marker, e.g., in exported histories
+                    let all_in = all_in || amount >= stacks[player] - antes[player] - 1e-9;
+                    HistoryAction::Raise(amount, all_in)
+                }
+                _ => continue,
+            };
+            actions.push((player, action));
+        }
+    }
+
+    Ok(HandHistory {
+        id: id.to_string(),
+        players: names,
+        stacks,
+        sb: blinds[0] / bb,
+        antes,
+        hero,
+        actions,
+    })
+}
+
+/// Parses an amount such as `1,500`, `$0.50` or `€2`.
+fn parse_amount(input: &str) -> Option<f64> {
+    let digits = input
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect::<String>();
+    digits.parse().ok()
+}
+
+/// Rounds an amount in big blinds for display.
+fn round(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(text: &str) -> Result<HandHistory, String> {
+        let mut hands = parse_histories(text);
+        assert_eq!(hands.len(), 1);
+        hands.pop().unwrap().1
+    }
+
+    #[test]
+    fn blind_all_in() {
+        let text = "\
+PokerStars Hand #208000000001: Tournament #3000000001, $1+$0.10 USD Hold'em No Limit - Level IV (50/100) - 2020/01/01 12:00:00 ET
+Table '3000000001 1' 2-max Seat #1 is the button
+Seat 1: alice (30 in chips)
+Seat 2: bob (2970 in chips)
+alice: posts small blind 30 and is all-in
+bob: posts big blind 100
+*** HOLE CARDS ***
+Dealt to alice [Ah Kd]
+Uncalled bet (70) returned to bob
+*** FLOP *** [2c 7d 9s]
+";
+        let hand = parse_one(text).unwrap();
+        assert_eq!(hand.players, ["alice".to_string(), "bob".to_string()]);
+        assert_eq!(hand.stacks, [0.3, 29.7]);
+        assert_eq!(hand.sb, 0.3);
+        assert_eq!(
+            hand.hero,
+            Some((0, [parse_card("Ah").unwrap(), parse_card("Kd").unwrap()]))
+        );
+        assert!(hand.actions.is_empty());
+    }
+
+    #[test]
+    fn uncalled_bet_returned() {
+        let text = "\
+PokerStars Hand #208000000002:  Hold'em No Limit ($0.50/$1.00 USD) - 2020/01/01 12:01:00 ET
+Table 'Foo' 2-max Seat #2 is the button
+Seat 1: alice ($100 in chips)
+Seat 2: bob ($1,250.50 in chips)
+bob: posts small blind $0.50
+alice: posts big blind $1
+*** HOLE CARDS ***
+Dealt to alice [7c 2d]
+bob: raises $1.50 to $2.50
+alice: raises $7.50 to $10
+bob: folds
+Uncalled bet ($7.50) returned to alice
+alice collected $5 from pot
+*** SUMMARY ***
+Total pot $5 | Rake $0
+";
+        let hand = parse_one(text).unwrap();
+        assert_eq!(hand.players, ["bob".to_string(), "alice".to_string()]);
+        assert_eq!(hand.stacks, [1250.5, 100.0]);
+        assert_eq!(hand.hero.map(|(seat, _)| seat), Some(1));
+        assert_eq!(
+            hand.actions,
+            vec![
+                (0, HistoryAction::Raise(2.5, false)),
+                (1, HistoryAction::Raise(10.0, false)),
+                (0, HistoryAction::Fold),
+            ]
+        );
+    }
+
+    #[test]
+    fn antes() {
+        let text = "\
+PokerStars Hand #208000000003: Tournament #3000000001, $1+$0.10 USD Hold'em No Limit - Level VI (100/200) - 2020/01/01 12:02:00 ET
+Table '3000000001 1' 2-max Seat #1 is the button
+Seat 1: alice (2000 in chips)
+Seat 2: bob (3000 in chips)
+alice: posts the ante 20
+bob: posts the ante 20
+alice: posts small blind 100
+bob: posts big blind 200
+*** HOLE CARDS ***
+Dealt to bob [Qs Qh]
+alice: raises 1880 to 1980 and is all-in
+bob: calls 1780
+*** FLOP *** [2c 7d 9s]
+";
+        let hand = parse_one(text).unwrap();
+        assert_eq!(hand.stacks, [10.0, 15.0]);
+        assert_eq!(hand.antes, [0.1, 0.1]);
+        assert_eq!(
+            hand.actions,
+            vec![
+                (0, HistoryAction::Raise(9.9, true)),
+                (1, HistoryAction::Call),
+            ]
+        );
+    }
+
+    #[test]
+    fn ante_all_in() {
+        // the player cannot post the big blind after the ante
+        let text = "\
+PokerStars Hand #208000000004: Tournament #3000000001, $1+$0.10 USD Hold'em No Limit - Level VI (100/200) - 2020/01/01 12:03:00 ET
+Table '3000000001 1' 2-max Seat #1 is the button
+Seat 1: alice (4990 in chips)
+Seat 2: bob (10 in chips)
+alice: posts the ante 20
+bob: posts the ante 10 and is all-in
+alice: posts small blind 100
+*** HOLE CARDS ***
+";
+        assert_eq!(parse_one(text).unwrap_err(), "missing blinds");
+    }
+}
@@ -0,0 +1,186 @@
+use crate::game_push_fold::Payoffs;
+
+/// Maximum number of players, since `equity` takes O(2^n n) time and O(2^n) memory for `n`
+/// players with chips.
+pub const MAX_PLAYERS: usize = 20;
+
+/// Computes each player's prize equity with the Malmuth-Harville model, i.e., the probability of
+/// finishing first is proportional to the stack, and the remaining places are assigned recursively
+/// among the remaining players in the same way. Players with no chips share the lowest places.
+pub fn equity(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+    let mut ret = vec![0.0; stacks.len()];
+    let alive = (0..stacks.len())
+        .filter(|&i| stacks[i] > 0.0)
+        .collect::<Vec<_>>();
+    assert!(alive.len() <= MAX_PLAYERS, "too many players");
+
+    // the probability of finishing the next place only depends on the set of players who have
+    // finished, so the probability of each set (a bitmask over `alive`) taking the first places
+    // is accumulated in increasing order of the masks
+    let mut prob = vec![0.0; 1 << alive.len()];
+    prob[0] = 1.0;
+    for used in 0..prob.len() {
+        let place = used.count_ones() as usize;
+        if prob[used] == 0.0 || place >= payouts.len() {
+            continue;
+        }
+        let remaining = (0..alive.len())
+            .filter(|bit| used & 1 << bit == 0)
+            .map(|bit| stacks[alive[bit]])
+            .sum::<f64>();
+        for (bit, &i) in alive.iter().enumerate() {
+            if used & 1 << bit != 0 {
+                continue;
+            }
+            let p = prob[used] * stacks[i] / remaining;
+            ret[i] += p * payouts[place];
+            prob[used | 1 << bit] += p;
+        }
+    }
+
+    // busted players split the payouts of the places after the alive players
+    let busted = stacks.len() - alive.len();
+    if busted > 0 {
+        let shared = payouts.iter().skip(alive.len()).take(busted).sum::<f64>() / busted as f64;
+        for (r, &stack) in ret.iter_mut().zip(stacks) {
+            if stack <= 0.0 {
+                *r = shared;
+            }
+        }
+    }
+
+    ret
+}
+
+/// Computes the push/fold payoffs of `pusher` and `caller` in prize equity. `stacks` are the
+/// stacks of all players before the hand, and `dead` (antes) and `live` (blinds) are the chips they
+/// have put into the pot before the pusher acts. Everyone else has folded, so their chips are dead
+/// money. At showdown, the pusher and the caller bet the smaller stack excluding the dead money.
+pub fn push_fold_payoffs(
+    stacks: &[f64],
+    dead: &[f64],
+    live: &[f64],
+    payouts: &[f64],
+    pusher: usize,
+    caller: usize,
+) -> Payoffs {
+    let posted = dead
+        .iter()
+        .zip(live)
+        .map(|(d, l)| d + l)
+        .collect::<Vec<_>>();
+    let pot = posted.iter().sum::<f64>();
+    let others = pot - posted[pusher] - posted[caller];
+    let all_in = (stacks[pusher] - dead[pusher]).min(stacks[caller] - dead[caller]);
+
+    let base = stacks
+        .iter()
+        .zip(&posted)
+        .map(|(stack, posted)| stack - posted)
+        .collect::<Vec<_>>();
+
+    let fold_to = |winner: usize| {
+        let mut ret = base.clone();
+        ret[winner] += pot;
+        ret
+    };
+
+    let showdown = |winner: usize, loser: usize| {
+        let mut ret = base.clone();
+        ret[winner] += posted[winner] + all_in + dead[loser] + others;
+        ret[loser] += live[loser] - all_in;
+        ret
+    };
+
+    let outcome = |stacks: Vec<f64>| {
+        let equity = equity(&stacks, payouts);
+        [equity[pusher], equity[caller]]
+    };
+
+    [
+        outcome(fold_to(caller)),
+        outcome(fold_to(pusher)),
+        outcome(showdown(pusher, caller)),
+        outcome(showdown(caller, pusher)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference implementation enumerating every order of finishers.
+    fn equity_by_orders(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+        fn assign_place(
+            stacks: &[f64],
+            payouts: &[f64],
+            place: usize,
+            used: &mut Vec<bool>,
+            prob: f64,
+            ret: &mut [f64],
+        ) {
+            let remaining = (0..stacks.len())
+                .filter(|&i| !used[i])
+                .map(|i| stacks[i])
+                .sum::<f64>();
+            if place >= payouts.len() || remaining <= 0.0 {
+                return;
+            }
+            for i in 0..stacks.len() {
+                if used[i] || stacks[i] <= 0.0 {
+                    continue;
+                }
+                let p = prob * stacks[i] / remaining;
+                ret[i] += p * payouts[place];
+                used[i] = true;
+                assign_place(stacks, payouts, place + 1, used, p, ret);
+                used[i] = false;
+            }
+        }
+        let mut ret = vec![0.0; stacks.len()];
+        let mut used = vec![false; stacks.len()];
+        assign_place(stacks, payouts, 0, &mut used, 1.0, &mut ret);
+        ret
+    }
+
+    fn assert_close(lhs: &[f64], rhs: &[f64]) {
+        assert_eq!(lhs.len(), rhs.len());
+        for (l, r) in lhs.iter().zip(rhs) {
+            assert!((l - r).abs() < 1e-12, "{:?} != {:?}", lhs, rhs);
+        }
+    }
+
+    #[test]
+    fn matches_orders() {
+        let stacks = [25.0, 15.0, 10.0, 10.0, 3.5, 40.0, 1.0];
+        for payouts in [
+            &[1.0][..],
+            &[50.0, 30.0, 20.0],
+            &[30.0, 25.0, 20.0, 10.0, 8.0, 5.0, 2.0],
+        ] {
+            assert_close(
+                &equity(&stacks, payouts),
+                &equity_by_orders(&stacks, payouts),
+            );
+        }
+    }
+
+    #[test]
+    fn busted_players_share() {
+        let equity = equity(&[20.0, 0.0, 10.0, 0.0], &[50.0, 30.0, 15.0, 5.0]);
+        assert_close(&equity[1..2], &[10.0]);
+        assert_close(&equity[3..4], &[10.0]);
+        assert!((equity.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn many_players() {
+        let stacks = (1..=MAX_PLAYERS).map(|i| i as f64).collect::<Vec<_>>();
+        let payouts = (0..MAX_PLAYERS)
+            .map(|i| (MAX_PLAYERS - i) as f64)
+            .collect::<Vec<_>>();
+        let equity = equity(&stacks, &payouts);
+        assert!((equity.iter().sum::<f64>() - payouts.iter().sum::<f64>()).abs() < 1e-6);
+        assert!(equity.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -0,0 +1,181 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of BB's raise sizes after a limp, i.e., the number of raise sizes elsewhere.
+pub const MAX_LIMP_RAISES: usize = 4;
+
+/// Options of the pre-flop tree after SB's first action. By default, SB may limp (call the big
+/// blind), BB then has the usual raise sizes, and BB's check ends the hand.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Limp {
+    /// Whether SB may limp
+    pub allowed: bool,
+    /// BB's raise sizes after a limp as fractions of the pot in increasing order (the array and
+    /// the number of sizes used), or `None` for the usual sizes
+    pub raises: Option<([f64; MAX_LIMP_RAISES], usize)>,
+    /// Whether BB's check after a limp ends the hand; otherwise SB may check or bet again (with
+    /// the same sizes as BB after a limp)
+    pub check_ends: bool,
+}
+
+impl Default for Limp {
+    fn default() -> Self {
+        Self {
+            allowed: true,
+            raises: None,
+            check_ends: true,
+        }
+    }
+}
+
+impl Limp {
+    /// Parses raise sizes such as `0.5,1` (or `none` for no raises but all-in).
+    pub fn parse_raises(input: &str) -> Result<([f64; MAX_LIMP_RAISES], usize), String> {
+        let mut ret = [0.0; MAX_LIMP_RAISES];
+        if input.trim() == "none" {
+            return Ok((ret, 0));
+        }
+        let sizes = input.split(',').map(|s| s.trim().parse::<f64>());
+        let mut len = 0;
+        for size in sizes {
+            match size {
+                Ok(size) if size > 0.0 && (len == 0 || size > ret[len - 1]) => {
+                    if len == MAX_LIMP_RAISES {
+                        return Err(format!("at most {} raise sizes", MAX_LIMP_RAISES));
+                    }
+                    ret[len] = size;
+                    len += 1;
+                }
+                _ => return Err(format!("invalid raise sizes '{}'", input)),
+            }
+        }
+        Ok((ret, len))
+    }
+
+    /// Returns BB's raise sizes after a limp as fractions of the pot (`None` for the usual sizes).
+    #[inline]
+    pub fn raise_sizes(&self) -> Option<&[f64]> {
+        self.raises.as_ref().map(|(sizes, len)| &sizes[..*len])
+    }
+
+    /// Returns the tag appended to output file names (empty for the default options).
+    pub fn tag(&self) -> String {
+        let mut ret = String::new();
+        if !self.allowed {
+            ret.push_str("-nolimp");
+        }
+        match self.raise_sizes() {
+            Some([]) => ret.push_str("-limpraisenone"),
+            Some(sizes) => ret.push_str(&format!("-limpraise{}", join(sizes, "_"))),
+            None => (),
+        }
+        if !self.check_ends {
+            ret.push_str("-reopen");
+        }
+        ret
+    }
+
+    /// Parses a tag generated by `tag`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let re = Regex::new(r"^(-nolimp)?(?:-limpraise(none|[\d._]+))?(-reopen)?$").unwrap();
+        let cs = re.captures(tag)?;
+        let raises = match cs.get(2) {
+            Some(sizes) => Some(Self::parse_raises(&sizes.as_str().replace('_', ",")).ok()?),
+            None => None,
+        };
+        Some(Self {
+            allowed: cs.get(1).is_none(),
+            raises,
+            check_ends: cs.get(3).is_none(),
+        })
+    }
+
+    /// Returns a human-readable description (empty for the default options).
+    pub fn description(&self) -> String {
+        let mut descriptions = Vec::new();
+        if !self.allowed {
+            descriptions.push("no limp".to_string());
+        }
+        match self.raise_sizes() {
+            Some([]) => descriptions.push("no raise after limp but all-in".to_string()),
+            Some(sizes) => descriptions.push(format!("raises after limp {} pot", join(sizes, "/"))),
+            None => (),
+        }
+        if !self.check_ends {
+            descriptions.push("SB acts after BB's check".to_string());
+        }
+        descriptions.join(", ")
+    }
+}
+
+fn join(sizes: &[f64], separator: &str) -> String {
+    let sizes = sizes.iter().map(|size| size.to_string());
+    sizes.collect::<Vec<_>>().join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_round_trip() {
+        let cases = [
+            Limp::default(),
+            Limp {
+                allowed: false,
+                ..Limp::default()
+            },
+            Limp {
+                raises: Some(([0.5, 1.0, 0.0, 0.0], 2)),
+                ..Limp::default()
+            },
+            Limp {
+                raises: Some(([0.0; MAX_LIMP_RAISES], 0)),
+                check_ends: false,
+                ..Limp::default()
+            },
+            Limp {
+                raises: Some(([0.33, 0.75, 1.5, 2.0], 4)),
+                check_ends: false,
+                ..Limp::default()
+            },
+        ];
+        for limp in cases.iter() {
+            assert_eq!(Limp::from_tag(&limp.tag()), Some(*limp));
+        }
+        assert_eq!(cases[0].tag(), "");
+        assert_eq!(cases[1].tag(), "-nolimp");
+        assert_eq!(cases[2].tag(), "-limpraise0.5_1");
+        assert_eq!(cases[3].tag(), "-limpraisenone-reopen");
+        assert_eq!(Limp::from_tag("-limpraise1_0.5"), None);
+        assert_eq!(Limp::from_tag("-reopen-nolimp"), None);
+        assert_eq!(Limp::from_tag("-limp"), None);
+    }
+
+    #[test]
+    fn raises() {
+        assert_eq!(Limp::parse_raises("0.5, 1"), Ok(([0.5, 1.0, 0.0, 0.0], 2)));
+        assert_eq!(Limp::parse_raises("none"), Ok(([0.0; MAX_LIMP_RAISES], 0)));
+        assert_eq!(
+            Limp::parse_raises("0.5,1,1.5,2,3"),
+            Err(format!("at most {} raise sizes", MAX_LIMP_RAISES))
+        );
+        for input in ["1,0.5", "0.5,0.5", "0", "-1", "x", ""].iter() {
+            assert!(Limp::parse_raises(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn descriptions() {
+        assert_eq!(Limp::default().description(), "");
+        let limp = Limp {
+            raises: Some(([0.5, 1.0, 0.0, 0.0], 2)),
+            check_ends: false,
+            ..Limp::default()
+        };
+        assert_eq!(
+            limp.description(),
+            "raises after limp 0.5/1 pot, SB acts after BB's check"
+        );
+    }
+}
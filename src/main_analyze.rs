@@ -0,0 +1,428 @@
+#[allow(dead_code)]
+mod action_translation;
+#[allow(dead_code)]
+mod blinds;
+#[allow(dead_code)]
+mod cards;
+#[allow(dead_code)]
+mod cfr;
+#[allow(dead_code)]
+mod game_info;
+#[allow(dead_code)]
+mod game_node;
+#[allow(dead_code)]
+mod game_preflop;
+mod hand_history;
+#[allow(dead_code)]
+mod limp;
+#[allow(dead_code)]
+mod preflop_equity;
+#[allow(dead_code)]
+mod rake;
+#[allow(dead_code)]
+mod rng;
+#[allow(dead_code)]
+mod showdown;
+#[allow(dead_code)]
+mod warm_start;
+
+use action_translation::{mix_str, pot_fraction, translate_raise};
+use cards::{combo_index, hand_str};
+use clap::Clap;
+use game_info::{action_token, GameInfo, GameSpec};
+use game_node::{GameNode, PublicInfoSet};
+use game_preflop::PreflopNode;
+use hand_history::{HandHistory, HistoryAction};
+use limp::Limp;
+use ordered_float::NotNan;
+use preflop_equity::{COMBO_CARDS, DEAL_PROB, NUM_COMBOS};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+use warm_start::Strategy;
+
+/// Frequency in the solution below which the hero's action counts as a deviation.
+const DEVIATION_THRESHOLD: f64 = 0.05;
+
+#[derive(Clap)]
+#[clap(version = "0.1.0", author = "Wataru Inariba <oinari17@gmail.com>")]
+struct Opts {
+    /// Hand history files in the PokerStars format
+    #[clap(required = true)]
+    files: Vec<String>,
+    /// Name of the player to analyze (default: the player whose hole cards are dealt)
+    #[clap(long)]
+    hero: Option<String>,
+    /// Directory of the output files of `preflop`
+    #[clap(long, default_value = "output")]
+    solutions: String,
+}
+
+/// Output file of `preflop` used to analyze hands.
+struct Solution {
+    path: PathBuf,
+    info: GameInfo,
+    root: PreflopNode,
+    strategy: Option<Strategy>,
+    /// Values of each action at the decisions analyzed so far, with the opponent's reach
+    values: HashMap<PublicInfoSet, (Vec<f64>, Vec<Vec<f64>>)>,
+}
+
+/// Hero's decision mapped onto the tree.
+struct Decision {
+    key: PublicInfoSet,
+    /// Pairs of action and probability (see `action_translation::translate_raise`)
+    actions: Vec<(usize, f64)>,
+    history: HistoryAction,
+}
+
+/// Totals over the analyzed hands.
+#[derive(Default)]
+struct Summary {
+    hands: usize,
+    skipped: usize,
+    decisions: usize,
+    deviations: usize,
+    ev_loss: f64,
+}
+
+fn main() {
+    let opts = Opts::parse();
+    if let Err(err) = analyze(&opts) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn analyze(opts: &Opts) -> Result<()> {
+    let mut solutions = find_solutions(&opts.solutions)?;
+    if solutions.is_empty() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "no pre-flop solutions with metadata in '{}'",
+                opts.solutions
+            ),
+        ));
+    }
+
+    let mut summary = Summary::default();
+    for file in &opts.files {
+        let text = fs::read_to_string(file)?;
+        for (id, hand) in hand_history::parse_histories(&text) {
+            let result = hand.and_then(|hand| {
+                let (seat, cards) = hero(&hand, opts)?;
+                let index = nearest_solution(&solutions, &hand);
+                analyze_hand(&mut solutions[index], &hand, seat, cards, &mut summary)
+            });
+            match result {
+                Ok(()) => summary.hands += 1,
+                Err(reason) => {
+                    println!("Hand #{}: skipped ({})", id, reason);
+                    summary.skipped += 1;
+                }
+            }
+        }
+    }
+
+    let unit = &solutions[0].info.unit;
+    let hands = summary.hands.max(1) as f64;
+    println!();
+    println!(
+        "Analyzed {} hands ({} skipped), {} decisions",
+        summary.hands, summary.skipped, summary.decisions
+    );
+    println!(
+        "Deviations: {} ({:.1}% of decisions)",
+        summary.deviations,
+        100.0 * summary.deviations as f64 / summary.decisions.max(1) as f64
+    );
+    println!(
+        "EV loss: {:.3}[{}] in total, {:.3}[{}]/100 hands",
+        summary.ev_loss,
+        unit,
+        100.0 * summary.ev_loss / hands,
+        unit
+    );
+    Ok(())
+}
+
+/// Lists the pre-flop output files with metadata in `dir`, keeping the file with the most
+/// iterations for each game.
+fn find_solutions(dir: &str) -> Result<Vec<Solution>> {
+    let re = Regex::new(r"^preflop-.+-(\d+)\.bin$").unwrap();
+    let mut games = HashMap::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+        let iteration = match re.captures(&file_name) {
+            Some(cs) => cs[1].parse::<usize>().unwrap(),
+            None => continue,
+        };
+        // files without metadata are skipped
+        let info = match game_info::load_info(path.to_str().unwrap()) {
+            Ok(info) => info,
+            Err(_) => continue,
+        };
+        let key = format!("{:?}", info.spec);
+        match games.get(&key) {
+            Some(&(prev, _, _)) if prev >= iteration => (),
+            _ => {
+                games.insert(key, (iteration, path, info));
+            }
+        }
+    }
+
+    let mut solutions = games
+        .into_iter()
+        .filter_map(|(_, (_, path, info))| match info.spec {
+            GameSpec::Preflop {
+                stacks,
+                blinds,
+                rake,
+                limp,
+            } => Some(Solution {
+                path,
+                root: PreflopNode::with_stacks(stacks, blinds)
+                    .with_rake(rake)
+                    .with_limp(limp),
+                info,
+                strategy: None,
+                values: HashMap::new(),
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    solutions.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(solutions)
+}
+
+/// Returns the hero's seat and hole cards.
+fn hero(hand: &HandHistory, opts: &Opts) -> std::result::Result<(usize, [usize; 2]), String> {
+    match (hand.hero, &opts.hero) {
+        (None, _) => Err("no hole cards".to_string()),
+        (Some((seat, _)), Some(name)) if &hand.players[seat] != name => {
+            Err(format!("{} is not dealt in", name))
+        }
+        (Some(hero), _) => Ok(hero),
+    }
+}
+
+/// Returns the index of the solution closest to `hand`: the same blinds and antes are preferred,
+/// then trees with the default limp options, then the nearest effective stack.
+fn nearest_solution(solutions: &[Solution], hand: &HandHistory) -> usize {
+    let hand_stack = (hand.stacks[0] - hand.antes[0]).min(hand.stacks[1] - hand.antes[1]);
+    let distance = |solution: &Solution| {
+        let (stacks, blinds, limp) = match solution.info.spec {
+            GameSpec::Preflop {
+                stacks,
+                blinds,
+                limp,
+                ..
+            } => (stacks, blinds, limp),
+            _ => unreachable!(),
+        };
+        let same_blinds = (blinds.sb - hand.sb).abs() < 1e-6
+            && (0..2).all(|player| (blinds.dead(player) - hand.antes[player]).abs() < 1e-6);
+        let stack = (stacks[0] - blinds.dead(0)).min(stacks[1] - blinds.dead(1));
+        (
+            !same_blinds,
+            limp != Limp::default(),
+            NotNan::new((stack - hand_stack).abs()).unwrap(),
+        )
+    };
+    (0..solutions.len())
+        .min_by_key(|&index| distance(&solutions[index]))
+        .unwrap()
+}
+
+/// Maps `hand` onto the tree of `solution` and prints the hero's decisions against the solution.
+fn analyze_hand(
+    solution: &mut Solution,
+    hand: &HandHistory,
+    seat: usize,
+    cards: [usize; 2],
+    summary: &mut Summary,
+) -> std::result::Result<(), String> {
+    let decisions = map_decisions(&solution.root, hand, seat)?;
+    if solution.strategy.is_none() {
+        let path = solution.path.to_str().unwrap();
+        let strategy = warm_start::load_combo_strategy(path).map_err(|err| err.to_string())?;
+        solution.strategy = Some(strategy);
+    }
+
+    let unit = solution.info.unit.clone();
+    let combo = combo_index(cards[0], cards[1]);
+    println!(
+        "Hand #{}: {} ({}) with {}, {:.1}bb vs {:.1}bb [{}]",
+        hand.id,
+        hand.players[seat],
+        solution.info.player_names[seat],
+        hand_str(cards),
+        hand.stacks[0],
+        hand.stacks[1],
+        solution.path.file_name().unwrap().to_string_lossy()
+    );
+
+    for decision in &decisions {
+        let evs = action_evs(solution, &decision.key, seat, combo);
+        let labels = &solution.info.action_labels[&decision.key];
+        let freqs = solution.strategy.as_ref().unwrap()[&decision.key]
+            .iter()
+            .map(|freq| freq[combo])
+            .collect::<Vec<_>>();
+        // an off-tree raise is scored as the mix of the actions it translates to
+        let expected = |values: &[f64]| {
+            let terms = decision.actions.iter();
+            terms
+                .map(|&(action, prob)| prob * values[action])
+                .sum::<f64>()
+        };
+        let freq = expected(&freqs);
+        let ev_loss = evs.as_ref().map_or(0.0, |evs| {
+            evs.iter().cloned().fold(f64::MIN, f64::max) - expected(evs)
+        });
+        let deviation = freq < DEVIATION_THRESHOLD;
+
+        println!(
+            "- {}: {} => {} ({:.1}%), EV loss {:.3}[{}]{}",
+            line_str(&solution.info, &decision.key),
+            decision.history.description(),
+            mix_str(&decision.actions, labels),
+            100.0 * freq,
+            ev_loss,
+            unit,
+            ["", " (deviation)"][deviation as usize]
+        );
+        let actions = labels.iter().enumerate().map(|(action, label)| {
+            let ev = evs
+                .as_ref()
+                .map_or(String::new(), |evs| format!(" ({:+.3})", evs[action]));
+            format!("{} {:.1}%{}", label, 100.0 * freqs[action], ev)
+        });
+        println!("  Solution: {}", actions.collect::<Vec<_>>().join(", "));
+
+        summary.decisions += 1;
+        summary.deviations += deviation as usize;
+        summary.ev_loss += ev_loss;
+    }
+    Ok(())
+}
+
+/// Maps the actions of `hand` onto the tree and returns the decisions of the player at `seat`.
+/// When a raise translates to a mix of actions, the line continues with the most likely one.
+fn map_decisions(
+    root: &PreflopNode,
+    hand: &HandHistory,
+    seat: usize,
+) -> std::result::Result<Vec<Decision>, String> {
+    let mut node = root.clone();
+    let mut decisions = Vec::new();
+    // bet faced in the hand, which may differ from the tree's when the stacks differ
+    let mut facing = 1.0;
+    let dead = hand.antes[0] + hand.antes[1];
+
+    for &(player, action) in &hand.actions {
+        // the rest cannot be mapped, e.g., after a raise mapped to a call of all-in
+        if node.is_terminal_node() {
+            break;
+        }
+        if player != node.current_player() {
+            return Err("unexpected order of actions".to_string());
+        }
+        let actions = map_action(&node, action, facing, dead);
+        let (mapped, _) = actions
+            .iter()
+            .cloned()
+            .max_by_key(|&(_, prob)| NotNan::new(prob).unwrap())
+            .unwrap();
+        if player == seat {
+            decisions.push(Decision {
+                key: node.public_info_set().clone(),
+                actions,
+                history: action,
+            });
+        }
+        if let HistoryAction::Raise(amount, _) = action {
+            facing = amount;
+        }
+        node = node.play(mapped);
+    }
+    Ok(decisions)
+}
+
+/// Maps `action` to the actions of `node` with probabilities, where `facing` is the bet faced in
+/// the hand and `dead` is the dead money. Raises are translated by their sizes as fractions of the
+/// pot (see `action_translation::translate_raise`), and all-in is mapped to all-in.
+fn map_action(
+    node: &PreflopNode,
+    action: HistoryAction,
+    facing: f64,
+    dead: f64,
+) -> Vec<(usize, f64)> {
+    match action {
+        HistoryAction::Fold => vec![(0, 1.0)],
+        // a limp maps to the smallest raise if the tree has no limp
+        HistoryAction::Call => translate_raise(node, 0.0),
+        HistoryAction::Raise(_, true) => vec![(node.num_actions() - 1, 1.0)],
+        HistoryAction::Raise(amount, false) => {
+            translate_raise(node, pot_fraction(amount, facing, dead))
+        }
+    }
+}
+
+/// Returns the hero's EV of each action at `key` with `combo`, or `None` if the opponent's range
+/// is empty there.
+fn action_evs(
+    solution: &mut Solution,
+    key: &[u8],
+    player: usize,
+    combo: usize,
+) -> Option<Vec<f64>> {
+    let strategy = solution.strategy.as_ref().unwrap();
+    let root = &solution.root;
+    let (pmi, values) = solution.values.entry(key.to_vec()).or_insert_with(|| {
+        let mut pmi = vec![1.0; NUM_COMBOS];
+        for (i, &action) in key.iter().enumerate() {
+            if i % 2 != player {
+                let freq = &strategy[&key[..i]][action as usize];
+                pmi.iter_mut().zip(freq).for_each(|(p, f)| *p *= f);
+            }
+        }
+        let values = cfr::compute_action_values(root, player, key, &pmi, strategy);
+        (pmi, values)
+    });
+
+    // the opponent's combos sharing no card with the hero's
+    let (c1, c2) = COMBO_CARDS[combo];
+    let reach = COMBO_CARDS
+        .iter()
+        .zip(pmi.iter())
+        .filter(|&(&(d1, d2), _)| d1 != c1 && d1 != c2 && d2 != c1 && d2 != c2)
+        .map(|(_, p)| p)
+        .sum::<f64>();
+    if reach == 0.0 {
+        return None;
+    }
+    Some(
+        values
+            .iter()
+            .map(|value| value[combo] / (DEAL_PROB * reach))
+            .collect(),
+    )
+}
+
+/// Returns the line before the decision `key`, e.g., `after R2.5 C`.
+fn line_str(info: &GameInfo, key: &[u8]) -> String {
+    if key.is_empty() {
+        return "First action".to_string();
+    }
+    let tokens = key.iter().enumerate().map(|(i, &action)| {
+        let prev = key[..i].to_vec();
+        action_token(&info.action_labels[&prev][action as usize])
+    });
+    format!("After {}", tokens.collect::<Vec<_>>().join(" "))
+}
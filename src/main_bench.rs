@@ -0,0 +1,357 @@
+#[allow(dead_code)]
+mod blinds;
+#[allow(dead_code)]
+mod cfr;
+
+#[allow(dead_code)]
+mod game_node;
+#[allow(dead_code)]
+mod game_preflop;
+#[allow(dead_code)]
+mod game_push_fold;
+#[allow(dead_code)]
+mod limp;
+#[allow(dead_code)]
+mod preflop_equity;
+#[allow(dead_code)]
+mod rake;
+#[allow(dead_code)]
+mod showdown;
+
+use bincode::serialize;
+use clap::Clap;
+use game_node::{GameNode, PublicInfoSet};
+use game_preflop::PreflopNode;
+use game_push_fold::PushFoldNode;
+use preflop_equity::{EQUITY_TOTAL, NUM_COMBOS};
+use showdown::ShowdownEvaluator;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::hint::black_box;
+use std::io::{Result, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const BENCH_DIR: &str = "target/bench/";
+const FIXTURE_PATH: &str = "target/bench/equity_fixture.bin";
+
+/// Minimum duration of each sample of cheap benchmarks (which are repeated within a sample).
+const SAMPLE_TIME: Duration = Duration::from_millis(20);
+
+#[derive(Clap)]
+#[clap(version = "0.1.0", author = "Wataru Inariba <oinari17@gmail.com>")]
+struct Opts {
+    /// Number of samples of each benchmark
+    #[clap(short = 'n', long, default_value = "10")]
+    samples: usize,
+    /// Solves over all 1326 combos instead of the 169 suit-isomorphic hand classes
+    #[clap(long)]
+    combos: bool,
+    /// Uses the given equity table instead of the generated fixture
+    #[clap(long)]
+    equity: Option<String>,
+    /// Saves the results as a baseline with the given name
+    #[clap(long)]
+    save_baseline: Option<String>,
+    /// Compares the results with the baseline of the given name
+    #[clap(long)]
+    baseline: Option<String>,
+    /// Runs only the benchmarks whose name contains this string
+    filter: Option<String>,
+}
+
+/// (mean, standard deviation, median, min) in milliseconds
+type Stats = (f64, f64, f64, f64);
+
+struct Bencher {
+    samples: usize,
+    filter: Option<String>,
+    baseline: HashMap<String, f64>,
+    results: Vec<(String, Stats)>,
+}
+
+fn main() -> Result<()> {
+    let opts = Opts::parse();
+
+    match &opts.equity {
+        Some(path) => preflop_equity::set_equity_path(path),
+        None => {
+            if !Path::new(FIXTURE_PATH).exists() {
+                println!("Generating equity fixture to '{}'...", FIXTURE_PATH);
+                fs::create_dir_all(BENCH_DIR)?;
+                let encoded = serialize(&generate_fixture()).unwrap();
+                let mut outfile = File::create(FIXTURE_PATH)?;
+                outfile.write_all(&encoded)?;
+            }
+            preflop_equity::set_equity_path(FIXTURE_PATH);
+        }
+    }
+
+    let baseline = match &opts.baseline {
+        Some(name) => load_baseline(name)?,
+        None => HashMap::new(),
+    };
+
+    let mut bencher = Bencher {
+        samples: opts.samples.max(1),
+        filter: opts.filter.clone(),
+        baseline,
+        results: Vec::new(),
+    };
+
+    println!(
+        "[Benchmark] ({}, {} samples each)",
+        ["hand classes", "combos"][opts.combos as usize],
+        bencher.samples
+    );
+    println!();
+    println!(
+        "{:<36} {:>11} {:>10} {:>11} {:>11} {:>9}",
+        "name", "mean[ms]", "sd[ms]", "median[ms]", "min[ms]", "change"
+    );
+
+    let preflop = |stack: f64| {
+        if opts.combos {
+            PreflopNode::new(stack)
+        } else {
+            PreflopNode::new_isomorphic(stack)
+        }
+    };
+    let push_fold = |stack: f64| {
+        if opts.combos {
+            PushFoldNode::new(stack)
+        } else {
+            PushFoldNode::new_isomorphic(stack)
+        }
+    };
+
+    // training iterations
+    for &stack in &[10.0, 40.0, 100.0] {
+        bencher.iterations(&format!("cfr_mt/preflop/{}bb", stack), &preflop(stack));
+    }
+    bencher.iterations("cfr/push_fold/10bb", &push_fold(10.0));
+
+    // exploitability and EV of a uniform strategy
+    let root = preflop(10.0);
+    let sigma = uniform_strategy(&root);
+    bencher.run("exploitability/preflop/10bb", || {
+        black_box(cfr::compute_exploitability(&root, &sigma));
+    });
+    let ones = vec![1.0; root.private_info_set_len()];
+    bencher.run("ev_detail/preflop/10bb", || {
+        let ev = Mutex::new(HashMap::new());
+        cfr::compute_ev_detail(&root, 0, &ones, &ones, &sigma, &ev);
+    });
+
+    // terminal node evaluation
+    let pmi = random_vector(root.private_info_set_len());
+    let preflop_fold = root.play(0);
+    let preflop_showdown = root.play(1).play(1);
+    bencher.run("evaluate/preflop/fold", || {
+        black_box(preflop_fold.evaluate(0, &pmi));
+    });
+    bencher.run("evaluate/preflop/showdown", || {
+        black_box(preflop_showdown.evaluate(0, &pmi));
+    });
+    let root = push_fold(10.0);
+    let push_fold_fold = root.play(0);
+    let push_fold_showdown = root.play(1).play(1);
+    bencher.run("evaluate/push_fold/fold", || {
+        black_box(push_fold_fold.evaluate(0, &pmi));
+    });
+    bencher.run("evaluate/push_fold/showdown", || {
+        black_box(push_fold_showdown.evaluate(0, &pmi));
+    });
+
+    // showdown evaluator for a known board (always over combos)
+    let board = [48, 25, 2, 47, 31];
+    let pmi = random_vector(NUM_COMBOS);
+    bencher.run("showdown/river/new", || {
+        black_box(ShowdownEvaluator::new(&board));
+    });
+    let evaluator = ShowdownEvaluator::new(&board);
+    bencher.run("showdown/river/evaluate", || {
+        black_box(evaluator.evaluate(&pmi, 1.0));
+    });
+
+    if let Some(name) = &opts.save_baseline {
+        let path = save_baseline(name, &bencher.results)?;
+        println!();
+        println!("Saved baseline to '{}'", path);
+    }
+
+    Ok(())
+}
+
+impl Bencher {
+    fn is_enabled(&self, name: &str) -> bool {
+        match &self.filter {
+            Some(filter) => name.contains(filter.as_str()),
+            None => true,
+        }
+    }
+
+    /// Measures the time of each training iteration (after one warm-up iteration).
+    fn iterations(&mut self, name: &str, root: &impl GameNode) {
+        if !self.is_enabled(name) {
+            return;
+        }
+        let multi_thread = name.starts_with("cfr_mt");
+        let mut times = cfr::time_iterations(root, self.samples + 1, multi_thread);
+        times.remove(0);
+        self.report(name, &times);
+    }
+
+    /// Measures the time of `f`. Cheap functions are repeated so that each sample takes at least
+    /// `SAMPLE_TIME`.
+    fn run(&mut self, name: &str, mut f: impl FnMut()) {
+        if !self.is_enabled(name) {
+            return;
+        }
+
+        let start = Instant::now();
+        f();
+        let warm_up = start.elapsed();
+        let repeat = (SAMPLE_TIME.as_secs_f64() / warm_up.as_secs_f64().max(1e-9)).ceil() as u32;
+        let repeat = repeat.max(1);
+
+        let mut times = Vec::with_capacity(self.samples);
+        for _ in 0..self.samples {
+            let start = Instant::now();
+            for _ in 0..repeat {
+                f();
+            }
+            times.push(start.elapsed() / repeat);
+        }
+        self.report(name, &times);
+    }
+
+    fn report(&mut self, name: &str, times: &[Duration]) {
+        let mut millis = times
+            .iter()
+            .map(|time| time.as_secs_f64() * 1000.0)
+            .collect::<Vec<_>>();
+        millis.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let len = millis.len() as f64;
+        let mean = millis.iter().sum::<f64>() / len;
+        let var = millis.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / len;
+        let median = millis[millis.len() / 2];
+        let min = millis[0];
+
+        let change = match self.baseline.get(name) {
+            Some(base) => format!("{:+.1}%", 100.0 * (mean / base - 1.0)),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<36} {:>11.3} {:>10.3} {:>11.3} {:>11.3} {:>9}",
+            name,
+            mean,
+            var.sqrt(),
+            median,
+            min,
+            change
+        );
+        self.results
+            .push((name.to_string(), (mean, var.sqrt(), median, min)));
+    }
+}
+
+/// Generates a synthetic equity table with the same layout as the real one. Equities are derived
+/// from a simple pre-flop hand strength heuristic, which is good enough for measuring throughput.
+fn generate_fixture() -> Vec<u32> {
+    let mut strength = Vec::with_capacity(NUM_COMBOS);
+    let mut cards = Vec::with_capacity(NUM_COMBOS);
+    for i in 0..51 {
+        for j in (i + 1)..52 {
+            let (lo, hi) = ((i / 4) as f64, (j / 4) as f64);
+            let mut s = hi + 0.5 * lo;
+            if i / 4 == j / 4 {
+                s += 8.0 + 0.5 * hi;
+            }
+            if i % 4 == j % 4 {
+                s += 1.5;
+            }
+            s -= 0.3 * (hi - lo - 1.0).clamp(0.0, 4.0);
+            strength.push(s);
+            cards.push((i, j));
+        }
+    }
+
+    let mut ret = vec![0; NUM_COMBOS * NUM_COMBOS];
+    for k1 in 0..NUM_COMBOS {
+        let (i, j) = cards[k1];
+        for k2 in 0..NUM_COMBOS {
+            let (m, n) = cards[k2];
+            if i != m && i != n && j != m && j != n {
+                let eq = 1.0 / (1.0 + (-(strength[k1] - strength[k2]) / 6.0).exp());
+                ret[k1 * NUM_COMBOS + k2] = (eq * EQUITY_TOTAL).round() as u32;
+            }
+        }
+    }
+
+    ret
+}
+
+/// Builds the strategy choosing every action uniformly at random.
+fn uniform_strategy(root: &impl GameNode) -> HashMap<PublicInfoSet, Vec<Vec<f64>>> {
+    fn build(node: &impl GameNode, sigma: &mut HashMap<PublicInfoSet, Vec<Vec<f64>>>) {
+        if node.is_terminal_node() {
+            return;
+        }
+        let num_actions = node.num_actions();
+        let prob = 1.0 / num_actions as f64;
+        sigma.insert(
+            node.public_info_set().clone(),
+            vec![vec![prob; node.private_info_set_len()]; num_actions],
+        );
+        for action in node.actions() {
+            build(&node.play(action), sigma);
+        }
+    }
+
+    let mut ret = HashMap::new();
+    build(root, &mut ret);
+    ret
+}
+
+/// Returns a deterministic pseudo-random vector in [0, 1).
+fn random_vector(len: usize) -> Vec<f64> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        })
+        .collect()
+}
+
+fn baseline_path(name: &str) -> String {
+    format!("{}baseline-{}.csv", BENCH_DIR, name)
+}
+
+fn load_baseline(name: &str) -> Result<HashMap<String, f64>> {
+    let content = fs::read_to_string(baseline_path(name))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let name = fields.next()?;
+            let mean = fields.next()?.parse().ok()?;
+            Some((name.to_string(), mean))
+        })
+        .collect())
+}
+
+fn save_baseline(name: &str, results: &[(String, Stats)]) -> Result<String> {
+    fs::create_dir_all(BENCH_DIR)?;
+    let path = baseline_path(name);
+    let mut outfile = File::create(&path)?;
+    for (name, (mean, sd, median, min)) in results {
+        writeln!(outfile, "{},{},{},{},{}", name, mean, sd, median, min)?;
+    }
+    Ok(path)
+}
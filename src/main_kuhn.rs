@@ -1,19 +1,36 @@
 #[allow(dead_code)]
+mod blinds;
+#[allow(dead_code)]
 mod cfr;
-
+#[allow(dead_code)]
+mod game_info;
 mod game_kuhn;
+#[allow(dead_code)]
 mod game_node;
+#[allow(dead_code)]
+mod limp;
+#[allow(dead_code)]
+mod rake;
 
+use bincode::serialize;
+use game_info::{GameInfo, GameSpec};
 use game_kuhn::KuhnNode;
-use std::collections::BTreeMap;
+use game_node::PublicInfoSet;
+use rake::Rake;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{Result, Write};
 
-fn main() {
-    kuhn(100000);
+fn main() -> Result<()> {
+    kuhn(100000)
 }
 
-fn kuhn(num_iter: usize) {
+fn kuhn(num_iter: usize) -> Result<()> {
     let kuhn_node = KuhnNode::new();
-    let (strategy, ev, exploitability) = cfr::train(&kuhn_node, num_iter, false);
+    let (strategy, ev, exploitability, _) = cfr::train(&kuhn_node, num_iter, None, None, false);
+    let path = format!("output/kuhn-{}.bin", num_iter);
+    write_result(&path, &kuhn_node, &strategy, ev, exploitability)?;
+
     let strategy = strategy
         .into_iter()
         .map(|(key, value)| (KuhnNode::public_info_set_str(&key), value))
@@ -37,4 +54,36 @@ fn kuhn(num_iter: usize) {
             );
         }
     }
+
+    println!();
+    println!("Wrote results to '{}'", path);
+    Ok(())
+}
+
+/// Writes the solution in the same format as `preflop` so that the viewer can show it. Each
+/// summarized grid is a single row of the cards.
+fn write_result(
+    path: &str,
+    root: &KuhnNode,
+    strategy: &HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+    ev: f64,
+    exploitability: f64,
+) -> Result<()> {
+    let summarized = strategy
+        .iter()
+        .map(|(key, value)| {
+            let grids = value
+                .iter()
+                .map(|row| vec![row.clone()])
+                .collect::<Vec<_>>();
+            (key.clone(), grids)
+        })
+        .collect::<HashMap<_, _>>();
+    let info = GameInfo::new(GameSpec::Kuhn, "", root);
+
+    std::fs::create_dir_all("output")?;
+    let encoded = serialize(&(summarized, ev, exploitability, Rake::default(), info)).unwrap();
+    File::create(path)?.write_all(&encoded)?;
+    let combo_path = format!("{}.combo.bin", path.trim_end_matches(".bin"));
+    File::create(combo_path)?.write_all(&serialize(strategy).unwrap())
 }
@@ -0,0 +1,356 @@
+#[allow(dead_code)]
+mod action_translation;
+#[allow(dead_code)]
+mod blinds;
+#[allow(dead_code)]
+mod cards;
+#[allow(dead_code)]
+mod cfr;
+#[allow(dead_code)]
+mod game_info;
+#[allow(dead_code)]
+mod game_node;
+#[allow(dead_code)]
+mod game_preflop;
+#[allow(dead_code)]
+mod game_push_fold;
+#[allow(dead_code)]
+mod limp;
+#[allow(dead_code)]
+mod preflop_equity;
+#[allow(dead_code)]
+mod rake;
+mod rng;
+#[allow(dead_code)]
+mod showdown;
+#[allow(dead_code)]
+mod warm_start;
+
+use action_translation::{mix_str, parse_raise, translate_multiple};
+use cards::{class_str, combo_index, deal, hand_str, showdown_winner};
+use clap::Clap;
+use game_info::{action_matches, action_token, GameInfo, GameSpec};
+use game_node::{HoldemNode, PublicInfoSet};
+use game_preflop::PreflopNode;
+use game_push_fold::PushFoldNode;
+use rng::Rng;
+use std::io::{BufRead, Error, ErrorKind, Result, Write};
+use std::str::FromStr;
+use warm_start::Strategy;
+
+/// Seat of the human player.
+#[derive(Clone, Copy)]
+enum Seat {
+    Sb,
+    Bb,
+    Alternate,
+}
+
+impl FromStr for Seat {
+    type Err = String;
+
+    fn from_str(input: &str) -> std::result::Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "sb" => Ok(Seat::Sb),
+            "bb" => Ok(Seat::Bb),
+            "alternate" => Ok(Seat::Alternate),
+            _ => Err(format!("unknown seat '{}' (sb, bb or alternate)", input)),
+        }
+    }
+}
+
+#[derive(Clap)]
+#[clap(version = "0.1.0", author = "Wataru Inariba <oinari17@gmail.com>")]
+struct Opts {
+    /// Output file of `preflop` or `push_fold` (e.g., `output/preflop-10-1000.bin`)
+    file: String,
+    /// Seat of the human player: sb, bb or alternate
+    #[clap(long, default_value = "alternate")]
+    seat: Seat,
+    /// Seed of the random number generator (default: current time)
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+/// Results of the human player in the session
+#[derive(Default)]
+struct Session {
+    /// (total payoff, number of hands) of each seat
+    results: [(f64, usize); 2],
+}
+
+fn main() {
+    let opts = Opts::parse();
+    if let Err(err) = play(&opts) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn play(opts: &Opts) -> Result<()> {
+    let info = game_info::load_info(&opts.file)?;
+    if let GameSpec::Kuhn = info.spec {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "only hold'em games can be played",
+        ));
+    }
+    let strategy = warm_start::load_combo_strategy(&opts.file)?;
+    let mut rng = opts.seed.map_or_else(Rng::from_time, Rng::new);
+
+    println!("Playing against '{}'", opts.file);
+    if !info.description.is_empty() {
+        println!("[{}]", info.description);
+    }
+    println!("Enter actions like F, C, X, R3 or A (or Q to quit).");
+
+    let session = match info.spec {
+        GameSpec::Preflop {
+            stacks,
+            blinds,
+            rake,
+            limp,
+        } => {
+            let root = PreflopNode::with_stacks(stacks, blinds)
+                .with_rake(rake)
+                .with_limp(limp);
+            play_session(
+                &root,
+                &info,
+                &strategy,
+                opts.seat,
+                translate_multiple,
+                &mut rng,
+            )?
+        }
+        GameSpec::PushFold {
+            stacks,
+            blinds,
+            rake,
+        } => {
+            let root = PushFoldNode::with_stacks(stacks, blinds).with_rake(rake);
+            play_session(
+                &root,
+                &info,
+                &strategy,
+                opts.seat,
+                |_, _| Vec::new(),
+                &mut rng,
+            )?
+        }
+        GameSpec::PushFoldPayoffs { payoffs } => {
+            let root = PushFoldNode::with_payoffs(payoffs);
+            play_session(
+                &root,
+                &info,
+                &strategy,
+                opts.seat,
+                |_, _| Vec::new(),
+                &mut rng,
+            )?
+        }
+        GameSpec::Kuhn => unreachable!(),
+    };
+
+    println!();
+    println!("Final result: {}", session_str(&session, &info));
+    Ok(())
+}
+
+/// Plays hands until the human player quits. `translate` maps a raise to the given multiple of
+/// the current bet to the actions of the tree with probabilities (empty if the game has no raise
+/// sizes to translate).
+fn play_session<N: HoldemNode>(
+    root: &N,
+    info: &GameInfo,
+    strategy: &Strategy,
+    seat: Seat,
+    translate: fn(&N, f64) -> Vec<(usize, f64)>,
+    rng: &mut Rng,
+) -> Result<Session> {
+    let mut session = Session::default();
+    let names = &info.player_names;
+    let unit = &info.unit;
+
+    let mut hand_number = 0;
+    loop {
+        hand_number += 1;
+        let seat = match seat {
+            Seat::Sb => 0,
+            Seat::Bb => 1,
+            Seat::Alternate => (hand_number + 1) % 2,
+        };
+        let dealt = deal(rng, 4, &[]);
+        let hands = [[dealt[0], dealt[1]], [dealt[2], dealt[3]]];
+        let combos = [
+            combo_index(dealt[0], dealt[1]),
+            combo_index(dealt[2], dealt[3]),
+        ];
+
+        println!();
+        println!(
+            "Hand #{}: you are {} with {} ({})",
+            hand_number,
+            names[seat],
+            hand_str(hands[seat]),
+            class_str(hands[seat])
+        );
+
+        // (public information set, chosen action) of each decision of the human player
+        let mut decisions = Vec::new();
+        let mut node = root.clone();
+        while !node.is_terminal_node() {
+            let player = node.current_player();
+            let key = node.public_info_set().clone();
+            let labels = &info.action_labels[&key];
+            let action = if player == seat {
+                match read_action(labels, |multiple| translate(&node, multiple), rng)? {
+                    Some(action) => action,
+                    None => return Ok(session),
+                }
+            } else {
+                let weights = strategy[&key]
+                    .iter()
+                    .map(|action| action[combos[player]])
+                    .collect::<Vec<_>>();
+                let action = rng.choose_weighted(&weights);
+                println!("{}: {}", names[player], labels[action]);
+                action
+            };
+            if player == seat {
+                decisions.push((key, action));
+            }
+            node = node.play(action);
+        }
+
+        // action 0 is fold in hold'em games
+        let winner = if node.public_info_set().last() == Some(&0) {
+            None
+        } else {
+            let board = deal(rng, 5, &dealt);
+            let board_str = board.iter().map(|&card| cards::card_str(card));
+            println!("Board: {}", board_str.collect::<Vec<_>>().join(" "));
+            showdown_winner(hands, &board)
+        };
+        let payoff = node.realized_payoff(seat, winner);
+        println!(
+            "{} had {} ({}). You {} {:.2}[{}]",
+            names[seat ^ 1],
+            hand_str(hands[seat ^ 1]),
+            class_str(hands[seat ^ 1]),
+            ["lose", "win"][(payoff >= 0.0) as usize],
+            payoff.abs(),
+            unit
+        );
+
+        for (key, action) in &decisions {
+            print_review(info, strategy, key, *action, hands[seat]);
+        }
+
+        session.results[seat].0 += payoff;
+        session.results[seat].1 += 1;
+        println!("Session: {}", session_str(&session, info));
+    }
+}
+
+/// Reads the human player's action, or `None` if the player quits. A raise of a size not in the
+/// tree (e.g., `R2.2`) is translated by `translate` to a mix of actions, from which the action is
+/// drawn.
+fn read_action(
+    labels: &[String],
+    translate: impl Fn(f64) -> Vec<(usize, f64)>,
+    rng: &mut Rng,
+) -> Result<Option<usize>> {
+    let choices = labels
+        .iter()
+        .map(|label| format!("{}: {}", action_token(label), label))
+        .collect::<Vec<_>>();
+    let stdin = std::io::stdin();
+    loop {
+        print!("Your action ({}): ", choices.join(" / "));
+        std::io::stdout().flush()?;
+        let mut input = String::new();
+        if stdin.lock().read_line(&mut input)? == 0 {
+            return Ok(None);
+        }
+        let input = input.trim();
+        if input.eq_ignore_ascii_case("q") {
+            return Ok(None);
+        }
+        if let Some(action) = labels.iter().position(|label| action_matches(input, label)) {
+            return Ok(Some(action));
+        }
+        match parse_raise(input).map(&translate) {
+            Some(mix) if !mix.is_empty() => {
+                let weights = mix.iter().map(|&(_, prob)| prob).collect::<Vec<_>>();
+                let action = mix[rng.choose_weighted(&weights)].0;
+                println!(
+                    "Translated to {} ({})",
+                    labels[action],
+                    mix_str(&mix, labels)
+                );
+                return Ok(Some(action));
+            }
+            _ => println!("Invalid action '{}'", input),
+        }
+    }
+}
+
+/// Prints the solution's frequencies for `hand` at the decision `key`.
+fn print_review(
+    info: &GameInfo,
+    strategy: &Strategy,
+    key: &PublicInfoSet,
+    action: usize,
+    hand: [usize; 2],
+) {
+    let labels = &info.action_labels[key];
+    let combo = combo_index(hand[0], hand[1]);
+    let line = if key.is_empty() {
+        "first action".to_string()
+    } else {
+        let tokens = key.iter().enumerate().map(|(i, &a)| {
+            let prev = key[..i].to_vec();
+            action_token(&info.action_labels[&prev][a as usize])
+        });
+        format!("after {}", tokens.collect::<Vec<_>>().join(" "))
+    };
+    let freqs = labels
+        .iter()
+        .zip(&strategy[key])
+        .map(|(label, freq)| format!("{} {:.1}%", label, 100.0 * freq[combo]))
+        .collect::<Vec<_>>();
+    println!(
+        "Solution for {} ({}): {} (you: {}, {:.1}%)",
+        hand_str(hand),
+        line,
+        freqs.join(", "),
+        labels[action],
+        100.0 * strategy[key][action][combo]
+    );
+}
+
+/// Returns the summary of the session, e.g., `10 hands, +3.50[bb] (+35.0[bb]/100 hands)`.
+fn session_str(session: &Session, info: &GameInfo) -> String {
+    let unit = &info.unit;
+    let total = session.results[0].0 + session.results[1].0;
+    let hands = session.results[0].1 + session.results[1].1;
+    let per_100 = |payoff: f64, hands: usize| 100.0 * payoff / hands.max(1) as f64;
+    let mut ret = format!(
+        "{} hands, {:+.2}[{}] ({:+.1}[{}]/100 hands)",
+        hands,
+        total,
+        unit,
+        per_100(total, hands),
+        unit
+    );
+    for (name, &(payoff, hands)) in info.player_names.iter().zip(&session.results) {
+        if hands > 0 {
+            ret.push_str(&format!(
+                "; as {}: {:+.2}[{}] in {} hands",
+                name, payoff, unit, hands
+            ));
+        }
+    }
+    ret
+}
@@ -1,72 +1,540 @@
 #[allow(dead_code)]
+mod blinds;
+#[allow(dead_code)]
 mod cfr;
 
+#[allow(dead_code)]
+mod game_info;
+#[allow(dead_code)]
 mod game_node;
+#[allow(dead_code)]
 mod game_preflop;
+#[allow(dead_code)]
+mod limp;
+#[allow(dead_code)]
+mod preflop_equity;
+#[allow(dead_code)]
+mod rake;
+#[allow(dead_code)]
+mod showdown;
+mod sweep;
+#[allow(dead_code)]
+mod warm_start;
 
 use bincode::{deserialize, serialize};
+use blinds::Blinds;
 use clap::Clap;
-use game_node::PublicInfoSet;
+use game_info::{GameInfo, GameSpec};
+use game_node::{GameNode, PublicInfoSet};
 use game_preflop::PreflopNode;
+use limp::Limp;
+use rake::Rake;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Result, Write};
 use std::path::Path;
+use std::time::Instant;
+use warm_start::Seed;
 
 #[derive(Clap)]
 #[clap(version = "0.1.0", author = "Wataru Inariba <oinari17@gmail.com>")]
 struct Opts {
     #[clap(short, long, default_value = "10.0")]
     stack: f64,
+    /// Stack of SB (defaults to `--stack`)
+    #[clap(long)]
+    sb_stack: Option<f64>,
+    /// Stack of BB (defaults to `--stack`)
+    #[clap(long)]
+    bb_stack: Option<f64>,
     #[clap(short, long, default_value = "1000")]
     iteration: usize,
+    /// Solves over all 1326 combos instead of the 169 suit-isomorphic hand classes
+    #[clap(long)]
+    combos: bool,
+    /// Prints the tree size and the estimated memory usage without training
+    #[clap(long)]
+    dry_run: bool,
+    /// Small blind
+    #[clap(long, default_value = "0.5")]
+    sb: f64,
+    /// Big blind
+    #[clap(long, default_value = "1.0")]
+    bb: f64,
+    /// Ante posted by each player
+    #[clap(long, default_value = "0.0")]
+    ante: f64,
+    /// Ante posted by the big blind only
+    #[clap(long, default_value = "0.0")]
+    bb_ante: f64,
+    /// Rake in percent of the pot
+    #[clap(long, default_value = "0.0")]
+    rake: f64,
+    /// Maximum rake in bb
+    #[clap(long)]
+    rake_cap: Option<f64>,
+    /// Takes no rake when the hand ends before the flop
+    #[clap(long)]
+    no_flop_no_drop: bool,
+    /// Disallows SB's limp (call of the big blind) as the first action
+    #[clap(long)]
+    no_limp: bool,
+    /// BB's raise sizes after a limp as fractions of the pot, e.g., `0.5,1` (`none` for all-in
+    /// only); defaults to the usual sizes
+    #[clap(long)]
+    limp_raises: Option<String>,
+    /// BB's check after a limp does not end the hand, and SB may check or bet again
+    #[clap(long)]
+    limp_reopen: bool,
+    /// Solves each of the given effective stacks, e.g., `7,10,15` or `20..100:10` (inclusive)
+    #[clap(long)]
+    sweep: Option<String>,
+    /// Stops training when the exploitability falls below this value in bb (checked every 100
+    /// iterations); `--iteration` is the maximum number of iterations
+    #[clap(long)]
+    target: Option<f64>,
+    /// Starts each depth of a sweep from the solution of the previous depth
+    #[clap(long)]
+    warm_start: bool,
+    /// Starts training from an existing output file (`.bin` or `.combo.bin`), which may have
+    /// different stacks or blinds
+    #[clap(long)]
+    warm_start_from: Option<String>,
+    /// Number of iterations the `--warm-start-from` strategy counts as in the average strategy
+    /// (e.g., the iterations of the file to resume it); 0 only seeds the regrets
+    #[clap(long, default_value = "0")]
+    warm_start_weight: usize,
+    /// Scale of the regrets seeded by a warm start relative to the largest difference of action
+    /// values (default: 3); a larger scale keeps the seed's actions longer
+    #[clap(long)]
+    warm_start_regret_scale: Option<f64>,
+}
+
+/// Result of solving one setting
+struct Solved {
+    strategy: HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+    /// EV of (SB, BB)
+    ev: [f64; 2],
+    exploitability: f64,
+    iterations: usize,
+    path: String,
 }
 
 fn main() -> Result<()> {
     let opts = Opts::parse();
-    let path = format!("output/preflop-{}-{}.bin", opts.stack, opts.iteration);
-    let file_exists = Path::new(&path).exists();
+    let blinds = Blinds {
+        sb: opts.sb,
+        bb: opts.bb,
+        ante: opts.ante,
+        bb_ante: opts.bb_ante,
+    };
+    let rake = Rake {
+        percent: opts.rake,
+        cap: opts.rake_cap,
+        no_flop_no_drop: opts.no_flop_no_drop,
+    };
+    let raises = match opts.limp_raises.as_deref().map(Limp::parse_raises) {
+        Some(Ok(raises)) => Some(raises),
+        Some(Err(message)) => {
+            eprintln!("error: {}", message);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    if opts.no_limp && (raises.is_some() || opts.limp_reopen) {
+        eprintln!("error: '--limp-raises' and '--limp-reopen' cannot be used with '--no-limp'");
+        std::process::exit(1);
+    }
+    let limp = Limp {
+        allowed: !opts.no_limp,
+        raises,
+        check_ends: !opts.limp_reopen,
+    };
 
-    println!(
-        "[Pre-flop only heads-up hold'em] (effective stack = {}bb)",
-        opts.stack
+    let seed = match &opts.warm_start_from {
+        Some(path) => Some(warm_start::load_seed(path)?),
+        None => None,
+    };
+
+    if let Some(sweep) = &opts.sweep {
+        let stacks = match sweep::parse_stacks(sweep) {
+            Ok(stacks) => stacks,
+            Err(message) => {
+                eprintln!("error: {}", message);
+                std::process::exit(1);
+            }
+        };
+        return run_sweep(&opts, &stacks, blinds, rake, limp, seed.as_ref());
+    }
+
+    let stacks = [
+        opts.sb_stack.unwrap_or(opts.stack),
+        opts.bb_stack.unwrap_or(opts.stack),
+    ];
+    print_title(stacks, blinds, rake, limp);
+    let preflop_node = build_node(&opts, stacks, blinds, rake, limp);
+
+    if opts.dry_run {
+        print_tree_stats(&preflop_node);
+        return Ok(());
+    }
+
+    // the number of iterations is not known in advance when `--target` is given, and a warm start
+    // is expected to give a different result
+    let path = output_path(stacks, blinds, rake, limp, opts.iteration);
+    let (ev, exploitability) =
+        if opts.target.is_none() && seed.is_none() && Path::new(&path).exists() {
+            load_result(&path, stacks, blinds, rake, limp)?
+        } else {
+            let warm_start = seed.map(|seed| {
+                let mapped = warm_start::map_strategy(&preflop_node, &seed);
+                (mapped, opts.warm_start_weight)
+            });
+            let warm_start = warm_start
+                .as_ref()
+                .map(|(mapped, weight)| (mapped, *weight, regret_scale(&opts)));
+            let solved = solve(&opts, &preflop_node, stacks, blinds, rake, limp, warm_start)?;
+            (solved.ev, solved.exploitability)
+        };
+
+    println!("- Exploitability: {:+.3e}[bb]", exploitability);
+    println!("- EV of SB: {:+.4}[bb]", ev[0]);
+    println!("- EV of BB: {:+.4}[bb]", ev[1]);
+
+    Ok(())
+}
+
+/// Solves each effective stack in `stacks` and writes a manifest of the results. With
+/// `--warm-start`, each depth starts from the solution of the previous one; otherwise (and for the
+/// first depth) from `seed` when given.
+fn run_sweep(
+    opts: &Opts,
+    stacks: &[f64],
+    blinds: Blinds,
+    rake: Rake,
+    limp: Limp,
+    seed: Option<&Seed>,
+) -> Result<()> {
+    let mut entries = Vec::new();
+    let mut prev_seed = None;
+
+    for &stack in stacks {
+        let stacks = [stack, stack];
+        println!();
+        print_title(stacks, blinds, rake, limp);
+        let preflop_node = build_node(opts, stacks, blinds, rake, limp);
+        if opts.dry_run {
+            print_tree_stats(&preflop_node);
+            continue;
+        }
+
+        let start = Instant::now();
+        let warm_start = match (prev_seed.as_ref().filter(|_| opts.warm_start), seed) {
+            (Some(prev), _) => Some((warm_start::map_strategy(&preflop_node, prev), 0)),
+            (None, Some(seed)) => Some((
+                warm_start::map_strategy(&preflop_node, seed),
+                opts.warm_start_weight,
+            )),
+            (None, None) => None,
+        };
+        let warm_start = warm_start
+            .as_ref()
+            .map(|(mapped, weight)| (mapped, *weight, regret_scale(opts)));
+        let solved = solve(opts, &preflop_node, stacks, blinds, rake, limp, warm_start)?;
+        println!(
+            "- Exploitability: {:+.3e}[bb] ({} iterations)",
+            solved.exploitability, solved.iterations
+        );
+
+        entries.push(sweep::SweepEntry {
+            stack,
+            iterations: solved.iterations,
+            ev: solved.ev,
+            exploitability: solved.exploitability,
+            seconds: start.elapsed().as_secs_f64(),
+            path: solved.path,
+        });
+        prev_seed = Some(Seed {
+            strategy: solved.strategy,
+            root: Some(preflop_node),
+        });
+    }
+
+    if opts.dry_run {
+        return Ok(());
+    }
+
+    sweep::print_summary(&entries);
+    let manifest_path = format!(
+        "output/preflop-sweep{}{}{}.csv",
+        blinds.tag(),
+        rake.tag(),
+        limp.tag()
     );
+    sweep::write_manifest(&manifest_path, &entries)?;
+    println!();
+    println!("Wrote manifest to '{}'", manifest_path);
+    Ok(())
+}
+
+/// Returns the scale of the regrets seeded by a warm start.
+fn regret_scale(opts: &Opts) -> f64 {
+    opts.warm_start_regret_scale
+        .unwrap_or(cfr::WARM_START_REGRET_SCALE)
+}
+
+fn print_title(stacks: [f64; 2], blinds: Blinds, rake: Rake, limp: Limp) {
+    let mut setting = if stacks[0] == stacks[1] {
+        format!("effective stack = {}bb", stacks[0])
+    } else {
+        format!("stacks = {}bb (SB) / {}bb (BB)", stacks[0], stacks[1])
+    };
+    let description = description(blinds, rake, limp);
+    if !description.is_empty() {
+        setting.push_str(&format!(", {}", description));
+    }
+    println!("[Pre-flop only heads-up hold'em] ({})", setting);
+}
 
-    let (_, ev, exploitability) = if file_exists {
-        let mut infile = File::open(&path)?;
+/// Returns the description of non-default blinds, rake and limp options.
+fn description(blinds: Blinds, rake: Rake, limp: Limp) -> String {
+    let descriptions = [blinds.description(), rake.description(), limp.description()];
+    let descriptions = descriptions.iter().filter(|d| !d.is_empty());
+    descriptions.cloned().collect::<Vec<_>>().join(", ")
+}
+
+fn build_node(
+    opts: &Opts,
+    stacks: [f64; 2],
+    blinds: Blinds,
+    rake: Rake,
+    limp: Limp,
+) -> PreflopNode {
+    let node = PreflopNode::with_stacks(stacks, blinds)
+        .with_rake(rake)
+        .with_limp(limp);
+    if opts.combos {
+        node
+    } else {
+        node.isomorphic()
+    }
+}
+
+fn output_path(stacks: [f64; 2], blinds: Blinds, rake: Rake, limp: Limp, iter: usize) -> String {
+    let stacks_str = if stacks[0] == stacks[1] {
+        format!("{}", stacks[0])
+    } else {
+        format!("{}vs{}", stacks[0], stacks[1])
+    };
+    format!(
+        "output/preflop-{}{}{}{}-{}.bin",
+        stacks_str,
+        blinds.tag(),
+        rake.tag(),
+        limp.tag(),
+        iter
+    )
+}
+
+/// Loads (EV of SB and BB, exploitability) from an existing output file.
+fn load_result(
+    path: &str,
+    stacks: [f64; 2],
+    blinds: Blinds,
+    rake: Rake,
+    limp: Limp,
+) -> Result<([f64; 2], f64)> {
+    let mut infile = File::open(path)?;
+    let mut buf = Vec::new();
+    infile.read_to_end(&mut buf)?;
+    let (_, ev, exploitability) =
+        deserialize::<(HashMap<PublicInfoSet, Vec<Vec<Vec<f64>>>>, f64, f64)>(&buf).unwrap();
+
+    // the game is not zero-sum under rake, so BB's EV is computed from the combo-level strategy
+    let bb_ev = if rake.is_zero() {
+        -ev
+    } else {
+        let mut infile = File::open(combo_path(path))?;
         let mut buf = Vec::new();
         infile.read_to_end(&mut buf)?;
-        deserialize::<(HashMap<PublicInfoSet, Vec<Vec<Vec<f64>>>>, f64, f64)>(&buf).unwrap()
-    } else {
-        let push_fold_node = PreflopNode::new(opts.stack);
-        let (raw_strategy, ev, exploitability) = cfr::train_mt(
-            &push_fold_node,
-            opts.iteration,
-            true,
-            Some((
-                |iter| format!("output/preflop-{}-{}.bin", opts.stack, iter),
-                summarize_strategy,
-            )),
+        let combo_strategy = deserialize::<HashMap<PublicInfoSet, Vec<Vec<f64>>>>(&buf).unwrap();
+        let ones = vec![1.0; preflop_equity::NUM_COMBOS];
+        let combo_node = PreflopNode::with_stacks(stacks, blinds)
+            .with_rake(rake)
+            .with_limp(limp);
+        cfr::compute_ev(&combo_node, 1, &ones, &ones, &combo_strategy)
+    };
+    Ok(([ev, bb_ev], exploitability))
+}
+
+/// Trains `preflop_node` and writes the summarized and combo-level strategies. The iterations of
+/// the output file include the weight of `warm_start`.
+#[allow(clippy::type_complexity)]
+fn solve(
+    opts: &Opts,
+    preflop_node: &PreflopNode,
+    stacks: [f64; 2],
+    blinds: Blinds,
+    rake: Rake,
+    limp: Limp,
+    warm_start: Option<(&HashMap<PublicInfoSet, Vec<Vec<f64>>>, usize, f64)>,
+) -> Result<Solved> {
+    let weight = warm_start.map_or(0, |(_, weight, _)| weight);
+    let (total, available) = estimate_memory(preflop_node);
+    if let Some(available) = available.filter(|&available| total > available) {
+        println!(
+            "Warning: training needs about {} but only {} is available",
+            format_bytes(total),
+            format_bytes(available)
         );
-        let converted = raw_strategy
+    }
+
+    let summarize = |strategy: &[Vec<f64>]| {
+        if opts.combos {
+            summarize_strategy(strategy)
+        } else {
+            preflop_equity::summarize_classes(strategy)
+        }
+    };
+    let spec = GameSpec::Preflop {
+        stacks,
+        blinds,
+        rake,
+        limp,
+    };
+    let info = GameInfo::new(spec, &description(blinds, rake, limp), preflop_node);
+    let (raw_strategy, ev, exploitability, iterations) = cfr::train_mt(
+        preflop_node,
+        opts.iteration,
+        opts.target,
+        warm_start,
+        true,
+        Some((
+            |iter| output_path(stacks, blinds, rake, limp, weight + iter),
+            summarize,
+            (rake, &info),
+        )),
+    );
+    let ones = vec![1.0; preflop_node.private_info_set_len()];
+    let bb_ev = cfr::compute_ev(preflop_node, 1, &ones, &ones, &raw_strategy);
+
+    let path = output_path(stacks, blinds, rake, limp, weight + iterations);
+    let converted = raw_strategy
+        .iter()
+        .map(|(key, value)| (key.clone(), summarize(value)))
+        .collect::<HashMap<_, _>>();
+    let encoded = serialize(&(converted, ev, exploitability, rake, &info)).unwrap();
+    let mut outfile = File::create(&path)?;
+    outfile.write_all(&encoded)?;
+    println!("Wrote results to '{}'", &path);
+
+    // keep combo-level strategy so that the viewer can show suit-specific mixing
+    let combo_path = combo_path(&path);
+    let combo_strategy = if opts.combos {
+        raw_strategy.clone()
+    } else {
+        raw_strategy
             .iter()
-            .map(|(key, value)| (key.clone(), summarize_strategy(value)))
-            .collect::<HashMap<_, _>>();
-        let encoded = serialize(&(converted, ev, exploitability)).unwrap();
-        let mut outfile = File::create(&path)?;
-        outfile.write_all(&encoded)?;
-        println!("Wrote results to '{}'", &path);
-        (HashMap::new(), ev, exploitability)
+            .map(|(key, value)| (key.clone(), preflop_equity::expand_classes(value)))
+            .collect()
     };
+    let encoded = serialize(&combo_strategy).unwrap();
+    let mut outfile = File::create(&combo_path)?;
+    outfile.write_all(&encoded)?;
+    println!("Wrote combo-level strategy to '{}'", &combo_path);
 
-    println!("- Exploitability: {:+.3e}[bb]", exploitability);
-    println!("- EV of SB: {:+.4}[bb]", ev);
-    println!("- EV of BB: {:+.4}[bb]", -ev);
+    Ok(Solved {
+        strategy: raw_strategy,
+        ev: [ev, bb_ev],
+        exploitability,
+        iterations,
+        path,
+    })
+}
 
-    Ok(())
+/// Prints the number of nodes, info sets and the estimated memory usage for each depth.
+fn print_tree_stats(root: &PreflopNode) {
+    let stats = cfr::tree_stats(root);
+    println!();
+    println!(
+        "{:>5} {:>10} {:>10} {:>12} {:>12}",
+        "depth", "public", "terminal", "info sets", "memory"
+    );
+    for (depth, &(public, terminal, info_sets, bytes)) in stats.iter().enumerate() {
+        println!(
+            "{:>5} {:>10} {:>10} {:>12} {:>12}",
+            depth,
+            public,
+            terminal,
+            info_sets,
+            format_bytes(bytes)
+        );
+    }
+
+    let sum = |f: fn(&(usize, usize, usize, usize)) -> usize| stats.iter().map(f).sum::<usize>();
+    println!(
+        "{:>5} {:>10} {:>10} {:>12} {:>12}",
+        "total",
+        sum(|s| s.0),
+        sum(|s| s.1),
+        sum(|s| s.2),
+        format_bytes(sum(|s| s.3))
+    );
+
+    let (total, available) = estimate_memory(root);
+    println!();
+    match available {
+        Some(available) => {
+            println!(
+                "Estimated memory usage: {} (available: {})",
+                format_bytes(total),
+                format_bytes(available)
+            );
+            if total > available {
+                println!("Warning: the estimate exceeds the available memory");
+            }
+        }
+        None => println!("Estimated memory usage: {}", format_bytes(total)),
+    }
+}
+
+/// Returns (estimated bytes needed for training, available bytes if known).
+fn estimate_memory(root: &PreflopNode) -> (usize, Option<usize>) {
+    let total = cfr::tree_stats(root).iter().map(|s| s.3).sum();
+    (total, available_memory())
+}
+
+/// Reads the available memory from `/proc/meminfo` (Linux only).
+fn available_memory() -> Option<usize> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let kib = line.split_whitespace().nth(1)?.parse::<usize>().ok()?;
+    Some(kib * 1024)
+}
+
+fn format_bytes(bytes: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < units.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+/// Returns the path of the combo-level strategy file corresponding to `path`.
+fn combo_path(path: &str) -> String {
+    format!("{}.combo.bin", path.trim_end_matches(".bin"))
 }
 
-fn summarize_strategy(strategy: &Vec<Vec<f64>>) -> Vec<Vec<Vec<f64>>> {
+fn summarize_strategy(strategy: &[Vec<f64>]) -> Vec<Vec<Vec<f64>>> {
     let num_actions = strategy.len();
     let mut summarized = vec![vec![vec![0.0; 13]; 13]; num_actions];
 
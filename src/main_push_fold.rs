@@ -1,116 +1,533 @@
 #[allow(dead_code)]
+mod blinds;
+#[allow(dead_code)]
 mod cfr;
 
+#[allow(dead_code)]
+mod game_info;
+#[allow(dead_code)]
 mod game_node;
+#[allow(dead_code)]
 mod game_push_fold;
+mod icm;
+#[allow(dead_code)]
+mod limp;
+#[allow(dead_code)]
+mod preflop_equity;
+#[allow(dead_code)]
+mod rake;
+#[allow(dead_code)]
+mod showdown;
+mod sweep;
 
+use bincode::serialize;
+use blinds::Blinds;
+use clap::Clap;
+use game_info::{GameInfo, GameSpec};
+use game_node::{GameNode, PublicInfoSet};
 use game_push_fold::PushFoldNode;
+use rake::Rake;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Result, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Instant;
+
+const RANKS: [&str; 13] = [
+    "2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K", "A",
+];
+
+/// Output format of the charts.
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(input: &str) -> std::result::Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format '{}' (table, csv or json)", input)),
+        }
+    }
+}
+
+#[derive(Clap)]
+#[clap(version = "0.1.0", author = "Wataru Inariba <oinari17@gmail.com>")]
+struct Opts {
+    /// Effective stack in bb (overridden by `--stacks`)
+    #[clap(short, long, default_value = "10.0")]
+    stack: f64,
+    #[clap(short, long, default_value = "10000")]
+    iteration: usize,
+    /// Output file of the solution (default: `output/push_fold-<stack>-<iteration>.bin`, or
+    /// `output/push_fold-icm-<stacks>-pay<payouts>-seat<pusher>-<iteration>.bin` with `--payouts`)
+    #[clap(short, long)]
+    output: Option<String>,
+    /// Format of the printed charts: table, csv or json
+    #[clap(long, default_value = "table")]
+    format: Format,
+    /// Payout structure (e.g., `50,30,20`); when given, solves the push of `--pusher` against BB
+    /// in prize equity under ICM, assuming everyone else folds
+    #[clap(long, use_delimiter = true)]
+    payouts: Vec<f64>,
+    /// Stacks of all players in bb in seat order, where the last two are SB and BB
+    /// (e.g., `25,15,10,10`); without `--payouts`, the stacks of SB and BB (e.g., `10,25`)
+    #[clap(long, use_delimiter = true)]
+    stacks: Vec<f64>,
+    /// Seat of the pusher (default: SB). Only the spot of the pusher against BB is modelled:
+    /// everyone else folds, and no other player may call
+    #[clap(long)]
+    pusher: Option<usize>,
+    /// Small blind
+    #[clap(long, default_value = "0.5")]
+    sb: f64,
+    /// Big blind
+    #[clap(long, default_value = "1.0")]
+    bb: f64,
+    /// Ante posted by each player
+    #[clap(long, default_value = "0.0")]
+    ante: f64,
+    /// Ante posted by the big blind only
+    #[clap(long, default_value = "0.0")]
+    bb_ante: f64,
+    /// Rake in percent of the pot (without `--payouts` only)
+    #[clap(long, default_value = "0.0")]
+    rake: f64,
+    /// Maximum rake in bb
+    #[clap(long)]
+    rake_cap: Option<f64>,
+    /// Takes no rake when the hand ends before the flop
+    #[clap(long)]
+    no_flop_no_drop: bool,
+    /// Solves each of the given effective stacks, e.g., `5,10,15` or `5..20:1` (inclusive)
+    #[clap(long)]
+    sweep: Option<String>,
+    /// Stops training when the exploitability falls below this value (checked every 100
+    /// iterations); `--iteration` is the maximum number of iterations
+    #[clap(long)]
+    target: Option<f64>,
+    /// Starts each depth of a sweep from the solution of the previous depth
+    #[clap(long)]
+    warm_start: bool,
+}
+
+/// Result of solving a push/fold game
+struct Solved {
+    strategy: HashMap<PublicInfoSet, Vec<Vec<f64>>>,
+    /// EV of (pusher, caller)
+    ev: [f64; 2],
+    exploitability: f64,
+    iterations: usize,
+}
 
 fn main() {
-    push_fold(10.0, 10000);
-}
-
-fn push_fold(eff_stack: f64, num_iter: usize) {
-    let push_fold_node = PushFoldNode::new(eff_stack);
-    let (strategy, ev, exploitability) = cfr::train(&push_fold_node, num_iter, false);
-    let pusher = &strategy[&vec![]];
-    let caller = &strategy[&vec![1]];
-
-    let mut push_rate = vec![vec![0.0; 13]; 13];
-    let mut call_rate = vec![vec![0.0; 13]; 13];
-    let mut overall_push_rate = 0.0;
-    let mut overall_call_rate = 0.0;
-
-    let mut k = 0;
-    for i in 0..51 {
-        for j in (i + 1)..52 {
-            let rank1 = i / 4;
-            let rank2 = j / 4;
-            let suit1 = i % 4;
-            let suit2 = j % 4;
-            if suit1 == suit2 {
-                push_rate[rank1][rank2] += pusher[1][k];
-                call_rate[rank1][rank2] += caller[1][k];
-            } else {
-                push_rate[rank2][rank1] += pusher[1][k];
-                call_rate[rank2][rank1] += caller[1][k];
+    let opts = Opts::parse();
+    let blinds = Blinds {
+        sb: opts.sb,
+        bb: opts.bb,
+        ante: opts.ante,
+        bb_ante: opts.bb_ante,
+    };
+    let rake = Rake {
+        percent: opts.rake,
+        cap: opts.rake_cap,
+        no_flop_no_drop: opts.no_flop_no_drop,
+    };
+
+    if let Some(sweep) = &opts.sweep {
+        if !opts.payouts.is_empty() {
+            eprintln!("error: `--sweep` cannot be used with `--payouts`");
+            std::process::exit(1);
+        }
+        if opts.output.is_some() {
+            eprintln!("error: `--sweep` cannot be used with `--output`");
+            std::process::exit(1);
+        }
+        match sweep::parse_stacks(sweep) {
+            Ok(stacks) => push_fold_sweep(&stacks, blinds, rake, &opts),
+            Err(message) => {
+                eprintln!("error: {}", message);
+                std::process::exit(1);
+            }
+        }
+    } else if opts.payouts.is_empty() {
+        let stacks = match opts.stacks.as_slice() {
+            [] => [opts.stack, opts.stack],
+            &[sb_stack, bb_stack] => [sb_stack, bb_stack],
+            _ => {
+                eprintln!("error: `--stacks` must list SB and BB stacks without `--payouts`");
+                std::process::exit(1);
             }
-            overall_push_rate += pusher[1][k];
-            overall_call_rate += caller[1][k];
-            k += 1;
+        };
+        let root = PushFoldNode::with_stacks(stacks, blinds)
+            .with_rake(rake)
+            .isomorphic();
+        let solved = solve(&root, opts.iteration, opts.target, None);
+        let title = chip_title(stacks, blinds, rake);
+        print_result(&solved, &title, "bb", opts.format);
+        let path = opts
+            .output
+            .clone()
+            .unwrap_or_else(|| output_path(stacks, blinds, rake, solved.iterations));
+        let info = chip_info(stacks, blinds, rake, &root);
+        save_result(&path, &solved, rake, &info, opts.format);
+    } else {
+        if !rake.is_zero() {
+            eprintln!("error: rake cannot be used with `--payouts`");
+            std::process::exit(1);
         }
+        push_fold_icm(&opts, &blinds);
     }
+}
 
-    for i in 0..13 {
-        for j in 0..13 {
-            let count = if i == j {
-                6.0
-            } else if i < j {
-                4.0
-            } else {
-                12.0
-            };
-            push_rate[i][j] /= count;
-            call_rate[i][j] /= count;
-        }
+fn chip_title(stacks: [f64; 2], blinds: Blinds, rake: Rake) -> String {
+    let mut setting = if stacks[0] == stacks[1] {
+        format!("effective stack = {}bb", stacks[0])
+    } else {
+        format!("stacks = {}bb (SB) / {}bb (BB)", stacks[0], stacks[1])
+    };
+    let description = chip_description(blinds, rake);
+    if !description.is_empty() {
+        setting.push_str(&format!(", {}", description));
     }
+    format!("[Push/Fold heads-up hold'em] ({})", setting)
+}
 
-    overall_push_rate /= 52.0 * 51.0 / 2.0;
-    overall_call_rate /= 52.0 * 51.0 / 2.0;
+/// Returns the description of non-default blinds and rake.
+fn chip_description(blinds: Blinds, rake: Rake) -> String {
+    let descriptions = [blinds.description(), rake.description()];
+    let descriptions = descriptions.iter().filter(|d| !d.is_empty());
+    descriptions.cloned().collect::<Vec<_>>().join(", ")
+}
 
+fn chip_info(stacks: [f64; 2], blinds: Blinds, rake: Rake, root: &PushFoldNode) -> GameInfo {
+    let spec = GameSpec::PushFold {
+        stacks,
+        blinds,
+        rake,
+    };
+    GameInfo::new(spec, &chip_description(blinds, rake), root)
+}
+
+/// Solves each effective stack in `stacks` in chips and writes a manifest of the results. With
+/// `--warm-start`, each depth starts from the solution of the previous one.
+fn push_fold_sweep(stacks: &[f64], blinds: Blinds, rake: Rake, opts: &Opts) {
+    let mut entries = Vec::new();
+    let mut prev_strategy = None;
+
+    for &stack in stacks {
+        let root = PushFoldNode::with_stacks([stack, stack], blinds)
+            .with_rake(rake)
+            .isomorphic();
+        let start = Instant::now();
+        let warm_start = prev_strategy
+            .as_ref()
+            .filter(|_| opts.warm_start)
+            .map(|prev| (prev, 0, cfr::WARM_START_REGRET_SCALE));
+        let solved = solve(&root, opts.iteration, opts.target, warm_start);
+        println!(
+            "{}: exploitability = {:+.3e}[bb] ({} iterations)",
+            chip_title([stack, stack], blinds, rake),
+            solved.exploitability,
+            solved.iterations
+        );
+        let path = output_path([stack, stack], blinds, rake, solved.iterations);
+        let info = chip_info([stack, stack], blinds, rake, &root);
+        if let Err(err) = write_result(&path, &solved, rake, &info) {
+            eprintln!("error: failed to write '{}': {}", path, err);
+            std::process::exit(1);
+        }
+
+        entries.push(sweep::SweepEntry {
+            stack,
+            iterations: solved.iterations,
+            ev: solved.ev,
+            exploitability: solved.exploitability,
+            seconds: start.elapsed().as_secs_f64(),
+            path,
+        });
+        prev_strategy = Some(solved.strategy);
+    }
+
+    sweep::print_summary(&entries);
+    let manifest_path = format!("output/push_fold-sweep{}{}.csv", blinds.tag(), rake.tag());
+    if let Err(err) = sweep::write_manifest(&manifest_path, &entries) {
+        eprintln!("error: failed to write '{}': {}", manifest_path, err);
+        std::process::exit(1);
+    }
     println!();
-    println!(
-        "[Push/Fold heads-up hold'em] (effective stack = {}bb)",
-        eff_stack
-    );
-    println!("- Exploitability: {:+.3e}[bb]", exploitability);
-    println!();
-    println!("Pusher (small blind):");
-    println!("- EV = {:+.4}[bb]", ev);
-    println!("- Overall push rate = {:.2}%", 100.0 * overall_push_rate);
-    println!(" |   A     K     Q     J     T     9     8     7     6     5     4     3     2");
-    println!("-+------------------------------------------------------------------------------");
-    for i in 0..13 {
-        let rank1 = 12 - i;
-        print!(
-            "{}|",
-            ["2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K", "A"][rank1]
+    println!("Wrote manifest to '{}'", manifest_path);
+}
+
+/// Solves the push/fold game in prize equity.
+fn push_fold_icm(opts: &Opts, blinds: &Blinds) {
+    let (stacks, payouts) = (&opts.stacks, &opts.payouts);
+    let num_players = stacks.len();
+    if !(2..=icm::MAX_PLAYERS).contains(&num_players) {
+        eprintln!(
+            "error: `--stacks` must list between 2 and {} players (ICM takes time and memory \
+             exponential in the number of players)",
+            icm::MAX_PLAYERS
         );
-        for j in 0..13 {
-            let rank2 = 12 - j;
-            if push_rate[rank2][rank1] >= 0.9995 {
-                print!(" 100.%");
-            } else if push_rate[rank2][rank1] < 0.0005 {
-                print!("   -  ");
-            } else {
-                print!(" {:>4.1}%", 100.0 * push_rate[rank2][rank1]);
+        std::process::exit(1);
+    }
+
+    let caller = num_players - 1;
+    let pusher = opts.pusher.unwrap_or(num_players - 2);
+    if pusher >= caller {
+        eprintln!("error: `--pusher` must be a seat before BB");
+        std::process::exit(1);
+    }
+
+    // everyone posts an ante, and BB posts the BB ante as well
+    let mut dead = vec![blinds.ante; num_players];
+    let mut live = vec![0.0; num_players];
+    dead[caller] += blinds.bb_ante;
+    live[num_players - 2] = blinds.sb;
+    live[caller] = blinds.bb;
+    for ((dead, live), &stack) in dead.iter_mut().zip(&mut live).zip(stacks) {
+        *dead = dead.min(stack);
+        *live = live.min(stack - *dead);
+    }
+
+    let before = icm::equity(stacks, payouts);
+    let payoffs = icm::push_fold_payoffs(stacks, &dead, &live, payouts, pusher, caller);
+    let title = format!(
+        "[Push/Fold under ICM] (stacks = {:?}bb, payouts = {:?}, pusher = seat {} vs BB)",
+        stacks, payouts, pusher
+    );
+
+    if opts.format == Format::Table {
+        println!();
+        println!("ICM equity before the hand:");
+        for (seat, (stack, equity)) in stacks.iter().zip(&before).enumerate() {
+            println!("- seat {}: {:>6}bb => {:.4}", seat, stack, equity);
+        }
+    }
+
+    let root = PushFoldNode::with_payoffs(payoffs).isomorphic();
+    let solved = solve(&root, opts.iteration, opts.target, None);
+    print_result(&solved, &title, "$", opts.format);
+    let path = opts
+        .output
+        .clone()
+        .unwrap_or_else(|| icm_output_path(stacks, payouts, pusher, *blinds, solved.iterations));
+    let spec = GameSpec::PushFoldPayoffs { payoffs };
+    let description = format!("ICM, payouts = {:?}, pusher = seat {}", payouts, pusher);
+    let info = GameInfo::new(spec, &description, &root);
+    save_result(&path, &solved, Rake::default(), &info, opts.format);
+}
+
+#[allow(clippy::type_complexity)]
+fn solve(
+    root: &PushFoldNode,
+    num_iter: usize,
+    target: Option<f64>,
+    warm_start: Option<(&HashMap<PublicInfoSet, Vec<Vec<f64>>>, usize, f64)>,
+) -> Solved {
+    let (strategy, ev, exploitability, iterations) =
+        cfr::train(root, num_iter, target, warm_start, false);
+    let ones = vec![1.0; root.private_info_set_len()];
+    let caller_ev = cfr::compute_ev(root, 1, &ones, &ones, &strategy);
+    Solved {
+        strategy,
+        ev: [ev, caller_ev],
+        exploitability,
+        iterations,
+    }
+}
+
+fn output_path(stacks: [f64; 2], blinds: Blinds, rake: Rake, iter: usize) -> String {
+    let stacks_str = if stacks[0] == stacks[1] {
+        format!("{}", stacks[0])
+    } else {
+        format!("{}vs{}", stacks[0], stacks[1])
+    };
+    format!(
+        "output/push_fold-{}{}{}-{}.bin",
+        stacks_str,
+        blinds.tag(),
+        rake.tag(),
+        iter
+    )
+}
+
+/// Returns the output path of an ICM solution, where lists are joined by `_` (e.g.,
+/// `output/push_fold-icm-25_15_10_10-pay50_30_20-seat2-10000.bin`).
+fn icm_output_path(
+    stacks: &[f64],
+    payouts: &[f64],
+    pusher: usize,
+    blinds: Blinds,
+    iter: usize,
+) -> String {
+    let join = |values: &[f64]| {
+        values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join("_")
+    };
+    format!(
+        "output/push_fold-icm-{}-pay{}-seat{}{}-{}.bin",
+        join(stacks),
+        join(payouts),
+        pusher,
+        blinds.tag(),
+        iter
+    )
+}
+
+/// Writes the solution in the same format as `preflop`, i.e., the summarized strategy with the
+/// EV, exploitability, rake and metadata, and the combo-level strategy in `*.combo.bin`.
+fn write_result(path: &str, solved: &Solved, rake: Rake, info: &GameInfo) -> Result<()> {
+    let summarized = solved
+        .strategy
+        .iter()
+        .map(|(key, value)| (key.clone(), preflop_equity::summarize_classes(value)))
+        .collect::<HashMap<_, _>>();
+    let combo_strategy = solved
+        .strategy
+        .iter()
+        .map(|(key, value)| (key.clone(), preflop_equity::expand_classes(value)))
+        .collect::<HashMap<_, _>>();
+
+    if let Some(dir) = Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let encoded = serialize(&(summarized, solved.ev[0], solved.exploitability, rake, info));
+    let encoded = encoded.unwrap();
+    File::create(path)?.write_all(&encoded)?;
+    let combo_path = format!("{}.combo.bin", path.trim_end_matches(".bin"));
+    File::create(combo_path)?.write_all(&serialize(&combo_strategy).unwrap())
+}
+
+/// Writes the solution to `path` and reports it, keeping stdout machine-readable for csv and json.
+/// Exits on failure.
+fn save_result(path: &str, solved: &Solved, rake: Rake, info: &GameInfo, format: Format) {
+    if let Err(err) = write_result(path, solved, rake, info) {
+        eprintln!("error: failed to write '{}': {}", path, err);
+        std::process::exit(1);
+    }
+    let message = format!("Wrote results to '{}'", path);
+    match format {
+        Format::Table => {
+            println!();
+            println!("{}", message);
+        }
+        _ => eprintln!("{}", message),
+    }
+}
+
+/// Prints the push and call rates of each hand class (and the overall rates) in `format`.
+fn print_result(solved: &Solved, title: &str, unit: &str, format: Format) {
+    let weights = preflop_equity::class_weights();
+    let num_combos = preflop_equity::NUM_COMBOS as f64;
+    let rates = [&vec![], &vec![1]].map(|key| {
+        let rate = solved.strategy[key][1].clone();
+        let overall = rate.iter().zip(&weights).map(|(r, w)| r * w).sum::<f64>() / num_combos;
+        (rate, overall)
+    });
+    let names = ["Pusher", "Caller (big blind)"];
+
+    match format {
+        Format::Table => {
+            println!();
+            println!("{}", title);
+            println!("- Exploitability: {:+.3e}[{}]", solved.exploitability, unit);
+            for (player, (rate, overall)) in rates.iter().enumerate() {
+                println!();
+                println!("{}:", names[player]);
+                println!("- EV = {:+.4}[{}]", solved.ev[player], unit);
+                println!(
+                    "- Overall {} rate = {:.2}%",
+                    ["push", "call"][player],
+                    100.0 * overall
+                );
+                print_chart(rate);
             }
         }
-        println!();
+        Format::Csv => {
+            println!("hand,push,call");
+            for (rank1, rank2) in display_order() {
+                let class = 13 * rank1 + rank2;
+                println!(
+                    "{},{},{}",
+                    class_str(rank1, rank2),
+                    rates[0].0[class],
+                    rates[1].0[class]
+                );
+            }
+        }
+        Format::Json => {
+            println!("{{");
+            println!("  \"title\": \"{}\",", json_escape(title));
+            println!("  \"unit\": \"{}\",", json_escape(unit));
+            println!("  \"exploitability\": {},", solved.exploitability);
+            for (player, (rate, overall)) in rates.iter().enumerate() {
+                println!("  \"{}\": {{", ["pusher", "caller"][player]);
+                println!("    \"ev\": {},", solved.ev[player]);
+                println!("    \"overall\": {},", overall);
+                let hands = display_order()
+                    .map(|(rank1, rank2)| {
+                        format!(
+                            "\"{}\": {}",
+                            class_str(rank1, rank2),
+                            rate[13 * rank1 + rank2]
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                println!("    \"hands\": {{{}}}", hands.join(", "));
+                println!("  }}{}", if player == 0 { "," } else { "" });
+            }
+            println!("}}");
+        }
     }
+}
 
-    println!();
-    println!("Caller (big blind): ");
-    println!("- EV = {:+.4}[bb]", -ev);
-    println!("- Overall call rate = {:.2}%", 100.0 * overall_call_rate);
+/// Prints the 13x13 chart of `rate` indexed by hand class.
+fn print_chart(rate: &[f64]) {
     println!(" |   A     K     Q     J     T     9     8     7     6     5     4     3     2");
     println!("-+------------------------------------------------------------------------------");
     for i in 0..13 {
         let rank1 = 12 - i;
-        print!(
-            "{}|",
-            ["2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K", "A"][rank1]
-        );
+        print!("{}|", RANKS[rank1]);
         for j in 0..13 {
             let rank2 = 12 - j;
-            if call_rate[rank2][rank1] >= 0.9995 {
+            let value = rate[13 * rank2 + rank1];
+            if value >= 0.9995 {
                 print!(" 100.%");
-            } else if call_rate[rank2][rank1] < 0.0005 {
+            } else if value < 0.0005 {
                 print!("   -  ");
             } else {
-                print!(" {:>4.1}%", 100.0 * call_rate[rank2][rank1]);
+                print!(" {:>4.1}%", 100.0 * value);
             }
         }
         println!();
     }
 }
+
+/// Returns the (row, column) of hand classes in the order of the charts, i.e., from AA to 22.
+fn display_order() -> impl Iterator<Item = (usize, usize)> {
+    (0..13).flat_map(|i| (0..13).map(move |j| (12 - j, 12 - i)))
+}
+
+fn class_str(rank1: usize, rank2: usize) -> String {
+    match rank1.cmp(&rank2) {
+        std::cmp::Ordering::Equal => format!("{}{}", RANKS[rank1], RANKS[rank2]),
+        std::cmp::Ordering::Less => format!("{}{}s", RANKS[rank2], RANKS[rank1]),
+        std::cmp::Ordering::Greater => format!("{}{}o", RANKS[rank1], RANKS[rank2]),
+    }
+}
+
+fn json_escape(input: &str) -> String {
+    input.replace('\\', "\\\\").replace('"', "\\\"")
+}
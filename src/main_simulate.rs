@@ -0,0 +1,354 @@
+#[allow(dead_code)]
+mod blinds;
+#[allow(dead_code)]
+mod cards;
+#[allow(dead_code)]
+mod cfr;
+#[allow(dead_code)]
+mod game_info;
+#[allow(dead_code)]
+mod game_node;
+#[allow(dead_code)]
+mod game_preflop;
+#[allow(dead_code)]
+mod game_push_fold;
+#[allow(dead_code)]
+mod limp;
+#[allow(dead_code)]
+mod preflop_equity;
+#[allow(dead_code)]
+mod rake;
+#[allow(dead_code)]
+mod rng;
+#[allow(dead_code)]
+mod showdown;
+#[allow(dead_code)]
+mod warm_start;
+
+use cards::{combo_index, deal, showdown_winner};
+use clap::Clap;
+use game_info::{GameInfo, GameSpec};
+use game_node::{GameNode, HoldemNode};
+use game_preflop::PreflopNode;
+use game_push_fold::PushFoldNode;
+use rng::Rng;
+use std::io::{Error, ErrorKind, Result};
+use std::time::Instant;
+use warm_start::Strategy;
+
+/// Deviation from the exact EV, in standard errors, above which the simulation is reported as
+/// inconsistent (the probability of a larger deviation by chance is about 0.3%).
+const MAX_DEVIATION: f64 = 3.0;
+
+#[derive(Clap)]
+#[clap(version = "0.1.0", author = "Wataru Inariba <oinari17@gmail.com>")]
+struct Opts {
+    /// Output file of player A (e.g., `output/preflop-10-1000.bin`)
+    file_a: String,
+    /// Output file of player B, which must be of the same game as player A
+    file_b: String,
+    /// Number of deals, each of which is played twice with the seats swapped
+    #[clap(short = 'n', long, default_value = "1000000")]
+    hands: usize,
+    /// Seed of the random number generator
+    #[clap(long, default_value = "0")]
+    seed: u64,
+}
+
+/// Running mean and variance of samples (Welford's algorithm).
+#[derive(Default)]
+struct Stats {
+    count: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl Stats {
+    fn push(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Returns the standard error of the mean.
+    fn std_error(&self) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+        (self.m2 / (self.count - 1) as f64 / self.count as f64).sqrt()
+    }
+}
+
+/// Player A's payoffs: `seats[p]` is A's payoff when seated as player `p`, and `total` is the
+/// average of both seats per deal.
+#[derive(Default)]
+struct Results {
+    seats: [Stats; 2],
+    total: Stats,
+}
+
+fn main() {
+    let opts = Opts::parse();
+    if let Err(err) = simulate(&opts) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn simulate(opts: &Opts) -> Result<()> {
+    let info = game_info::load_info(&opts.file_a)?;
+    let info_b = game_info::load_info(&opts.file_b)?;
+    if let GameSpec::Kuhn = info.spec {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "only hold'em games can be simulated",
+        ));
+    }
+    if format!("{:?}", info.spec) != format!("{:?}", info_b.spec)
+        || info.action_labels != info_b.action_labels
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "the files are solutions of different games",
+        ));
+    }
+    let strategies = [
+        warm_start::load_combo_strategy(&opts.file_a)?,
+        warm_start::load_combo_strategy(&opts.file_b)?,
+    ];
+
+    println!("A: '{}'", opts.file_a);
+    println!("B: '{}'", opts.file_b);
+    if !info.description.is_empty() {
+        println!("[{}]", info.description);
+    }
+
+    match info.spec {
+        GameSpec::Preflop {
+            stacks,
+            blinds,
+            rake,
+            limp,
+        } => {
+            let root = PreflopNode::with_stacks(stacks, blinds)
+                .with_rake(rake)
+                .with_limp(limp);
+            run(&root, &info, &strategies, opts)
+        }
+        GameSpec::PushFold {
+            stacks,
+            blinds,
+            rake,
+        } => {
+            let root = PushFoldNode::with_stacks(stacks, blinds).with_rake(rake);
+            run(&root, &info, &strategies, opts)
+        }
+        GameSpec::PushFoldPayoffs { payoffs } => {
+            let root = PushFoldNode::with_payoffs(payoffs);
+            run(&root, &info, &strategies, opts)
+        }
+        GameSpec::Kuhn => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Simulates the match between `strategies[0]` (A) and `strategies[1]` (B) and compares the
+/// result with the exact EV.
+fn run(root: &impl HoldemNode, info: &GameInfo, strategies: &[Strategy; 2], opts: &Opts) {
+    let unit = &info.unit;
+    let names = &info.player_names;
+
+    let start = Instant::now();
+    let exact = [
+        exact_ev(root, 0, [&strategies[0], &strategies[1]]),
+        exact_ev(root, 1, [&strategies[1], &strategies[0]]),
+    ];
+    let exact_total = (exact[0] + exact[1]) / 2.0;
+    println!(
+        "Exact EV of A (compute_ev): {:+.3}[{}]/100 hands ({:.1}s)",
+        100.0 * exact_total,
+        unit,
+        start.elapsed().as_secs_f64()
+    );
+
+    println!(
+        "Simulating {} deals x 2 seats (seed {})...",
+        opts.hands, opts.seed
+    );
+    let start = Instant::now();
+    let mut rng = Rng::new(opts.seed);
+    let results = simulate_hands(root, strategies, opts.hands, &mut rng, |rng, hands| {
+        showdown_winner(hands, &deal(rng, 5, &hands.concat()))
+    });
+    println!("Done in {:.1}s", start.elapsed().as_secs_f64());
+
+    println!();
+    println!("Win rate of A [{}/100 hands, 95% CI]:", unit);
+    println!("- Total: {}", win_rate_str(&results.total, exact_total));
+    for (name, (stats, &exact)) in names.iter().zip(results.seats.iter().zip(&exact)) {
+        println!("- As {}: {}", name, win_rate_str(stats, exact));
+    }
+
+    let deviation = (results.total.mean - exact_total) / results.total.std_error();
+    if deviation.abs() <= MAX_DEVIATION {
+        println!(
+            "OK: the simulation agrees with the exact EV ({:+.2} standard errors)",
+            deviation
+        );
+    } else {
+        println!(
+            "Warning: the simulation deviates from the exact EV by {:+.2} standard errors",
+            deviation
+        );
+    }
+}
+
+/// Returns the EV of player `player` when `strategies[p]` plays the decisions of player `p`.
+fn exact_ev(root: &impl GameNode, player: usize, strategies: [&Strategy; 2]) -> f64 {
+    let mut profile = Strategy::new();
+    merge_strategies(root, strategies, &mut profile);
+    let ones = vec![1.0; root.private_info_set_len()];
+    cfr::compute_ev(root, player, &ones, &ones, &profile)
+}
+
+/// Builds the strategy profile below `node` where `strategies[p]` plays player `p`'s decisions.
+fn merge_strategies(node: &impl GameNode, strategies: [&Strategy; 2], profile: &mut Strategy) {
+    if node.is_terminal_node() {
+        return;
+    }
+    let key = node.public_info_set();
+    let strategy = strategies[node.current_player()][key].clone();
+    profile.insert(key.clone(), strategy);
+    for action in node.actions() {
+        merge_strategies(&node.play(action), strategies, profile);
+    }
+}
+
+/// Plays `deals` deals, each of which is played twice with the same cards for each seat: once
+/// with A as SB and B as BB, and once the other way around. Pairing the seats this way cancels
+/// most of the luck of the cards. `showdown` returns the winner between two hands (`None` for a
+/// split pot), drawing the board from `rng`.
+fn simulate_hands(
+    root: &impl HoldemNode,
+    strategies: &[Strategy; 2],
+    deals: usize,
+    rng: &mut Rng,
+    showdown: impl Fn(&mut Rng, [[usize; 2]; 2]) -> Option<usize>,
+) -> Results {
+    let mut results = Results::default();
+    for _ in 0..deals {
+        let dealt = deal(rng, 4, &[]);
+        let hands = [[dealt[0], dealt[1]], [dealt[2], dealt[3]]];
+        let combos = [
+            combo_index(dealt[0], dealt[1]),
+            combo_index(dealt[2], dealt[3]),
+        ];
+        let winner = showdown(rng, hands);
+
+        let mut total = 0.0;
+        for seat_a in 0..2 {
+            let seated = match seat_a {
+                0 => [&strategies[0], &strategies[1]],
+                _ => [&strategies[1], &strategies[0]],
+            };
+            let mut node = root.clone();
+            while !node.is_terminal_node() {
+                let player = node.current_player();
+                let weights = seated[player][node.public_info_set()]
+                    .iter()
+                    .map(|action| action[combos[player]])
+                    .collect::<Vec<_>>();
+                node = node.play(rng.choose_weighted(&weights));
+            }
+            let payoff = node.realized_payoff(seat_a, winner);
+            results.seats[seat_a].push(payoff);
+            total += payoff;
+        }
+        results.total.push(total / 2.0);
+    }
+    results
+}
+
+/// Returns the win rate per 100 hands with its 95% confidence interval and the exact value.
+fn win_rate_str(stats: &Stats, exact: f64) -> String {
+    let margin = 1.96 * stats.std_error();
+    format!(
+        "{:+.3} ± {:.3} ({:+.3} to {:+.3}; exact {:+.3})",
+        100.0 * stats.mean,
+        100.0 * margin,
+        100.0 * (stats.mean - margin),
+        100.0 * (stats.mean + margin),
+        100.0 * exact
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use preflop_equity::tests::use_fixture;
+    use preflop_equity::{EQUITY_TABLE, EQUITY_TOTAL, NUM_COMBOS};
+
+    /// Fills `strategy` with random mixed strategies for every combo at every decision node.
+    fn random_strategy(node: &impl GameNode, rng: &mut Rng, strategy: &mut Strategy) {
+        if node.is_terminal_node() {
+            return;
+        }
+        let weights = (0..node.private_info_set_len())
+            .map(|_| {
+                let weights = (0..node.num_actions())
+                    .map(|_| rng.next_f64())
+                    .collect::<Vec<_>>();
+                let sum = weights.iter().sum::<f64>();
+                weights
+                    .iter()
+                    .map(|weight| weight / sum)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let probs = (0..node.num_actions())
+            .map(|action| weights.iter().map(|combo| combo[action]).collect())
+            .collect();
+        strategy.insert(node.public_info_set().clone(), probs);
+        for action in node.actions() {
+            random_strategy(&node.play(action), rng, strategy);
+        }
+    }
+
+    #[test]
+    fn converges_to_exact_ev() {
+        use_fixture();
+        let root = PreflopNode::new(3.0);
+        let mut rng = Rng::new(42);
+        let mut strategies = [Strategy::new(), Strategy::new()];
+        for strategy in &mut strategies {
+            random_strategy(&root, &mut rng, strategy);
+        }
+        let exact_total = (exact_ev(&root, 0, [&strategies[0], &strategies[1]])
+            + exact_ev(&root, 1, [&strategies[1], &strategies[0]]))
+            / 2.0;
+
+        // the fixture is not a real equity table, so draw the winner from it instead of a board
+        let results = simulate_hands(&root, &strategies, 20000, &mut rng, |rng, hands| {
+            let combos = [
+                combo_index(hands[0][0], hands[0][1]),
+                combo_index(hands[1][0], hands[1][1]),
+            ];
+            let equity = EQUITY_TABLE[combos[0] * NUM_COMBOS + combos[1]] as f64 / EQUITY_TOTAL;
+            if rng.next_f64() < equity {
+                Some(0)
+            } else {
+                Some(1)
+            }
+        });
+        let margin = 1.96 * results.total.std_error();
+        assert!(margin > 0.0);
+        assert!(
+            (results.total.mean - exact_total).abs() <= margin,
+            "simulated {} ± {}, exact {}",
+            results.total.mean,
+            margin,
+            exact_total
+        );
+    }
+}
@@ -1,26 +1,103 @@
 #[allow(dead_code)]
+mod action_translation;
+#[allow(dead_code)]
+mod blinds;
+#[allow(dead_code)]
+mod cards;
+#[allow(dead_code)]
 mod cfr;
+#[allow(dead_code)]
+mod game_info;
+#[allow(dead_code)]
+mod game_kuhn;
+#[allow(dead_code)]
 mod game_node;
+#[allow(dead_code)]
 mod game_preflop;
+#[allow(dead_code)]
+mod game_push_fold;
+#[allow(dead_code)]
+mod limp;
+#[allow(dead_code)]
+mod preflop_equity;
+#[allow(dead_code)]
+mod rake;
+mod rng;
+#[allow(dead_code)]
+mod showdown;
+mod viewer_cache;
+mod viewer_export;
+mod viewer_line;
 
-use bincode::deserialize;
+use bincode::{deserialize, serialize};
+use blinds::Blinds;
+use cards::{card_str, combo_index};
 use crossterm::{
     cursor,
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     execute, queue, style, terminal,
 };
-use game_node::PublicInfoSet;
+use game_info::{action_token, GameInfo, GameSpec, HandLayout};
+use game_node::{GameNode, PublicInfoSet};
+use limp::Limp;
+use once_cell::unsync::OnceCell;
 use ordered_float::NotNan;
+use rake::Rake;
 use regex::Regex;
+use rng::Rng;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use viewer_line::{class_combos, parse_actions, parse_file, parse_hand_class, split_line};
 
 type OutputType = (HashMap<PublicInfoSet, Vec<Vec<Vec<f64>>>>, f64, f64);
-type EvType = HashMap<PublicInfoSet, Vec<Vec<f64>>>;
+type ComboType = HashMap<PublicInfoSet, Vec<Vec<f64>>>;
+type ComboEvType = HashMap<PublicInfoSet, Vec<f64>>;
+
+const RANKS: [&str; 13] = [
+    "2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K", "A",
+];
+
+/// Games listed by the viewer, in the order of the file list (prefixes of the file names).
+const GAMES: [&str; 3] = ["preflop", "push_fold", "kuhn"];
+
+/// Frequency (and reach) below which an action does not count as a part of mixing.
+const MIX_THRESHOLD: f64 = 0.05;
+
+const OUTPUT_DIR: &str = "output/";
+const CACHE_DIR: &str = "output/cache/";
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+
+struct OutputFile {
+    /// Game of the file (an element of `GAMES`)
+    game: &'static str,
+    /// Stacks of SB and BB, if the setting of the file name starts with them
+    stacks: Option<[NotNan<f64>; 2]>,
+    blinds: Blinds,
+    /// Rake part of the file name (see `Rake::tag`)
+    rake_tag: String,
+    /// Limp part of the file name (see `Limp::tag`)
+    limp_tag: String,
+    /// Setting part of the file name, e.g., `10vs25-ante0.1` (empty for Kuhn poker)
+    setting: String,
+    path: PathBuf,
+    solution: OnceCell<Solution>,
+}
+
+struct Solution {
+    output: OutputType,
+    info: GameInfo,
+    /// EV of each player, which are not zero-sum under rake or ICM
+    ev: [f64; 2],
+    /// Whether `combo_strategy` was read from a combo-level file (otherwise it is restored from
+    /// the summarized strategy and does not contain suit-specific mixing).
+    has_combo_data: bool,
+    combo_strategy: ComboType,
+    combo_ev: [ComboEvType; 2],
+}
 
 fn main() {
-    println!("Loading files. Please wait...");
     let outputs = get_outputs().unwrap();
     if outputs.is_empty() {
         println!("Error: 'output' directory is empty.");
@@ -30,43 +107,258 @@ fn main() {
         println!();
         return;
     }
+    viewer_cache::prune(Path::new(CACHE_DIR), Path::new(OUTPUT_DIR)).unwrap();
     interactive_display(&outputs).unwrap();
 }
 
-fn get_outputs() -> std::io::Result<Vec<(NotNan<f64>, (OutputType, EvType, EvType))>> {
-    let output_path = "output/";
-    let re = Regex::new(r"^preflop-(\d+.?\d*)-(\d+).bin$").unwrap();
+fn get_outputs() -> std::io::Result<Vec<OutputFile>> {
+    let re = Regex::new(r"^([a-z_]+)-(?:(.+)-)?(\d+)\.bin$").unwrap();
+    let setting_re = Regex::new(concat!(
+        r"^(\d+\.?\d*)(?:vs(\d+\.?\d*))?",
+        r"((?:-(?:sb|bb|ante|bbante)\d+\.?\d*)*)",
+        r"((?:-rake\d+\.?\d*(?:-cap\d+\.?\d*)?(?:-nfnd)?)?)",
+        r"((?:-nolimp)?(?:-limpraise(?:none|\d+\.?\d*(?:_\d+\.?\d*)*))?(?:-reopen)?)$",
+    ))
+    .unwrap();
     let mut outputs = BTreeMap::new();
-    let mut iterations = BTreeMap::new();
 
-    for entry in fs::read_dir(output_path)? {
+    for entry in fs::read_dir(OUTPUT_DIR)? {
         let path = entry?.path();
         let file_name = path.file_name().unwrap().to_string_lossy().to_string();
 
-        if let Some(cs) = re.captures(&file_name) {
-            let stack = cs.get(1).unwrap().as_str().parse::<NotNan<f64>>().unwrap();
-            let iteration = cs.get(2).unwrap().as_str().parse::<usize>().unwrap();
+        let cs = match re.captures(&file_name) {
+            Some(cs) => cs,
+            None => continue,
+        };
+        let game_index = match GAMES.iter().position(|&game| game == &cs[1]) {
+            Some(index) => index,
+            None => continue,
+        };
+        let setting = cs.get(2).map_or("", |m| m.as_str()).to_string();
+        let iteration = cs[3].parse::<usize>().unwrap();
+
+        let mut output = OutputFile {
+            game: GAMES[game_index],
+            stacks: None,
+            blinds: Blinds::default(),
+            rake_tag: String::new(),
+            limp_tag: String::new(),
+            setting: setting.clone(),
+            path,
+            solution: OnceCell::new(),
+        };
+        if let Some(cs) = setting_re.captures(&setting) {
+            let sb_stack = cs[1].parse::<NotNan<f64>>().unwrap();
+            let bb_stack = match cs.get(2) {
+                Some(m) => m.as_str().parse::<NotNan<f64>>().unwrap(),
+                None => sb_stack,
+            };
+            output.blinds = match Blinds::from_tag(&cs[3]) {
+                Some(blinds) => blinds,
+                None => continue,
+            };
+            output.stacks = Some([sb_stack, bb_stack]);
+            output.rake_tag = cs[4].to_string();
+            output.limp_tag = cs[5].to_string();
+        } else if output.game == "preflop" {
+            // the viewer needs the stacks to show pre-flop files without metadata
+            continue;
+        }
 
-            if !iterations.contains_key(&stack) || iteration > iterations[&stack] {
-                let mut infile = File::open(path)?;
-                let mut buf = Vec::new();
-                infile.read_to_end(&mut buf).unwrap();
-                let decoded = deserialize::<OutputType>(&buf).unwrap();
-                let ev0 = calc_ev(&decoded.0, 0, stack.into_inner());
-                let ev1 = calc_ev(&decoded.0, 1, stack.into_inner());
-                outputs.insert(stack, (decoded, ev0, ev1));
-                iterations.insert(stack, iteration);
+        // keep the file with the most iterations for each setting
+        let key = (
+            game_index,
+            output.stacks,
+            output.blinds.tag(),
+            output.rake_tag.clone(),
+            output.limp_tag.clone(),
+            output.setting.clone(),
+        );
+        match outputs.get(&key) {
+            Some(&(prev, _)) if prev >= iteration => (),
+            _ => {
+                outputs.insert(key, (iteration, output));
             }
         }
     }
 
-    let outputs = outputs.into_iter().collect::<Vec<_>>();
-    Ok(outputs)
+    Ok(outputs.into_values().map(|(_, output)| output).collect())
 }
 
-fn interactive_display(
-    outputs: &Vec<(NotNan<f64>, (OutputType, EvType, EvType))>,
-) -> crossterm::Result<()> {
+impl OutputFile {
+    /// Returns the solution, loading it on first access.
+    fn load(&self) -> std::io::Result<&Solution> {
+        self.solution.get_or_try_init(|| load_solution(self))
+    }
+
+    /// Returns the solution, which must have been loaded successfully by `load`.
+    fn solution(&self) -> &Solution {
+        self.solution.get().expect("the solution is not loaded")
+    }
+
+    /// Returns the stacks like `40` or `10/25` (SB/BB).
+    fn stacks_str(&self) -> String {
+        match self.stacks {
+            Some([sb_stack, bb_stack]) if sb_stack == bb_stack => format!("{}", sb_stack),
+            Some([sb_stack, bb_stack]) => format!("{}/{}", sb_stack, bb_stack),
+            None => String::new(),
+        }
+    }
+
+    /// Returns the label in the file list.
+    fn label(&self) -> String {
+        let is_default = self.blinds == Blinds::default()
+            && self.rake_tag.is_empty()
+            && self.limp_tag.is_empty();
+        let plus = ["", "+"][!is_default as usize];
+        match (self.game, self.stacks) {
+            ("preflop", _) => format!("{:>3}bb{}", self.stacks_str(), plus),
+            ("push_fold", Some(_)) => format!("PF {:>3}bb{}", self.stacks_str(), plus),
+            ("push_fold", None) => format!("PF {}", self.setting),
+            _ => "Kuhn".to_string(),
+        }
+    }
+
+    /// Returns the name used in lines, e.g., `40bb-ante0.1` for pre-flop files, `pf10bb` for
+    /// push/fold files and `kuhn`.
+    fn name(&self) -> String {
+        match (self.game, self.stacks) {
+            ("preflop", _) => format!("{}bb{}", self.stacks_str(), self.tag()),
+            ("push_fold", Some(_)) => format!("pf{}bb{}", self.stacks_str(), self.tag()),
+            (game, _) if self.setting.is_empty() => game.to_string(),
+            (game, _) => format!("{}-{}", game, self.setting),
+        }
+    }
+
+    /// Returns the tag of the blinds, the rake and the limp options, e.g., `-ante0.1-rake5-cap3`,
+    /// or the setting when it does not start with stacks.
+    fn tag(&self) -> String {
+        match self.stacks {
+            Some(_) => format!("{}{}{}", self.blinds.tag(), self.rake_tag, self.limp_tag),
+            None => self.setting.clone(),
+        }
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.solution.get().is_some()
+    }
+
+    /// Returns the metadata of a file written before the metadata was stored, which is
+    /// reconstructed from the file name.
+    fn legacy_info(&self, rake: Rake) -> GameInfo {
+        let stacks = self
+            .stacks
+            .expect("the file has neither metadata nor stacks");
+        let stacks = [stacks[0].into_inner(), stacks[1].into_inner()];
+        let blinds = self.blinds;
+        let description = [blinds.description(), rake.description()]
+            .iter()
+            .filter(|description| !description.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.game {
+            "preflop" => {
+                let node = game_preflop::PreflopNode::with_stacks(stacks, blinds);
+                let spec = GameSpec::Preflop {
+                    stacks,
+                    blinds,
+                    rake,
+                    limp: Limp::default(),
+                };
+                GameInfo::new(spec, &description, &node)
+            }
+            _ => {
+                let node = game_push_fold::PushFoldNode::with_stacks(stacks, blinds);
+                let spec = GameSpec::PushFold {
+                    stacks,
+                    blinds,
+                    rake,
+                };
+                GameInfo::new(spec, &description, &node)
+            }
+        }
+    }
+}
+
+fn load_solution(output: &OutputFile) -> std::io::Result<Solution> {
+    let path = &output.path;
+    let invalid = |path: &Path| {
+        let message = format!("invalid file '{}'", path.display());
+        move |_| std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+    };
+    let mut infile = File::open(path)?;
+    let mut buf = Vec::new();
+    infile.read_to_end(&mut buf)?;
+    let decoded = deserialize::<OutputType>(&buf).map_err(invalid(path))?;
+    // older files do not store the rake and the metadata
+    let rake = deserialize::<(OutputType, Rake)>(&buf)
+        .map(|(_, rake)| rake)
+        .unwrap_or_default();
+    let info = deserialize::<(OutputType, Rake, GameInfo)>(&buf)
+        .map(|(_, _, info)| info)
+        .unwrap_or_else(|_| output.legacy_info(rake));
+    let mut hash = fnv1a_hash(FNV_OFFSET_BASIS, &buf);
+
+    let combo_path = path.with_extension("combo.bin");
+    let (has_combo_data, combo_strategy) = if combo_path.exists() {
+        let mut infile = File::open(&combo_path)?;
+        let mut buf = Vec::new();
+        infile.read_to_end(&mut buf)?;
+        hash = fnv1a_hash(hash, &buf);
+        let combo_strategy = deserialize::<ComboType>(&buf).map_err(invalid(&combo_path))?;
+        (true, combo_strategy)
+    } else {
+        let restored = decoded
+            .0
+            .iter()
+            .map(|(key, value)| (key.clone(), info.hand_layout.restore_strategy(value)))
+            .collect();
+        (false, restored)
+    };
+
+    // EV tables only depend on the contents of the files and the game, so they are cached by hash
+    let setting = format!("{:?}", info.spec);
+    hash = fnv1a_hash(hash, setting.as_bytes());
+    let cache_path = viewer_cache::entry_path(Path::new(CACHE_DIR), path, hash);
+    let combo_ev = if cache_path.exists() {
+        let mut infile = File::open(&cache_path)?;
+        let mut buf = Vec::new();
+        infile.read_to_end(&mut buf)?;
+        deserialize::<[ComboEvType; 2]>(&buf).map_err(invalid(&cache_path))?
+    } else {
+        let combo_ev0 = calc_ev(&combo_strategy, 0, &info.spec);
+        let combo_ev1 = calc_ev(&combo_strategy, 1, &info.spec);
+        let combo_ev = [combo_ev0, combo_ev1];
+        let encoded = serialize(&combo_ev).unwrap();
+        viewer_cache::store(&cache_path, &encoded)?;
+        combo_ev
+    };
+
+    // EVs are not zero-sum under rake or ICM
+    let bb_ev = combo_ev[1][&Vec::new()].iter().sum::<f64>();
+    let ev = [decoded.1, bb_ev];
+
+    Ok(Solution {
+        output: decoded,
+        info,
+        ev,
+        has_combo_data,
+        combo_strategy,
+        combo_ev,
+    })
+}
+
+/// Computes 64-bit FNV-1a hash of `bytes` starting from `hash`.
+fn fnv1a_hash(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn interactive_display(outputs: &[OutputFile]) -> crossterm::Result<()> {
     let mut stdout = std::io::stdout();
 
     terminal::enable_raw_mode()?;
@@ -79,45 +371,128 @@ fn interactive_display(
     )?;
 
     let (terminal_width, terminal_height) = terminal::size()?;
+    let mut layout = Layout::new(terminal_width, terminal_height);
     let mut files_top_index = 0;
     let mut indices = vec![0];
     let mut num_indices = vec![outputs.len()];
-    let mut output: &OutputType = &(HashMap::new(), 0.0, 0.0);
-    let mut ev0_table: &EvType = &HashMap::new();
-    let mut ev1_table: &EvType = &HashMap::new();
+    let files_width = outputs
+        .iter()
+        .map(|output| output.label().len() + 3)
+        .max()
+        .unwrap_or(0)
+        .max(8);
+    let mut solution: Option<&Solution> = None;
     let mut display_mode = 0;
     let mut multiple_select: HashSet<usize> = HashSet::new();
+    let mut grid_cursor = (0, 0);
+    let mut grid_scroll = 0;
+    let mut show_combos = false;
+    let mut prompt: Option<(char, String)> = None;
+    let mut message = String::new();
+    let mut search_results: Vec<Vec<usize>> = Vec::new();
+    let mut search_pos = 0;
+    let mut rng = Rng::from_time();
+    let mut quiz: Option<Question> = None;
+    let mut quiz_stats = QuizStats::default();
 
     loop {
-        queue!(
-            stdout,
-            terminal::Clear(terminal::ClearType::All),
-            cursor::MoveTo(0, 0),
-            style::Print(
-                "'q': Quit / 't': Toggle content / Space: Multi-select / Arrow keys: Move cursor"
-            ),
-        )?;
+        queue!(stdout, terminal::Clear(terminal::ClearType::All))?;
 
-        let (col_display_start, offset) = if 23 * indices.len() - 15 > terminal_width as usize {
-            queue!(stdout, cursor::MoveTo(0, 5), style::Print("(omitted)"))?;
-            let col_display_start = indices.len() - (terminal_width as usize - 10) / 23;
-            (col_display_start, 23 * col_display_start - 10)
+        if let Some(question) = &quiz {
+            draw_quiz(&mut stdout, &layout, outputs, question, &quiz_stats)?;
+            stdout.flush().unwrap();
+            match event::read()? {
+                Event::Key(key_ev)
+                    if key_ev == KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL) =>
+                {
+                    break
+                }
+                Event::Key(key_ev) => match key_ev.code {
+                    KeyCode::Char(c) if c.is_ascii_digit() && question.answer.is_none() => {
+                        let answer = (c as usize).wrapping_sub('1' as usize);
+                        if answer < question.freqs.len() {
+                            quiz_stats.record(question, answer);
+                            quiz.as_mut().unwrap().answer = Some(answer);
+                        }
+                    }
+                    KeyCode::Enter if question.answer.is_some() => {
+                        let file_index = quiz_file(outputs, question.line[0], &mut rng);
+                        if !outputs[file_index].is_loaded() {
+                            show_loading(&mut stdout, &layout, &outputs[file_index])?;
+                        }
+                        quiz = match outputs[file_index].load() {
+                            Ok(_) => Some(sample_question(outputs, file_index, &mut rng)),
+                            Err(err) => {
+                                message = format!("Error: {}", err);
+                                None
+                            }
+                        };
+                    }
+                    KeyCode::Esc | KeyCode::Char('p') => {
+                        let unit = &outputs[question.line[0]].solution().info.unit;
+                        message = format!("Quiz: {}", quiz_stats.summary(unit));
+                        quiz = None;
+                    }
+                    _ => (),
+                },
+                Event::Resize(width, height) => {
+                    layout = Layout::new(width, height);
+                }
+                _ => (),
+            }
+            continue;
+        }
+
+        if layout.compact {
+            layout.print(
+                &mut stdout,
+                0,
+                0,
+                "q:Quit p:Quiz t:Toggle Space:Multi hjkl:Hand c:Combos :Line /Search n/N e:Export",
+            )?;
         } else {
-            (1, 15)
-        };
+            layout.print(
+                &mut stdout,
+                0,
+                0,
+                "'q': Quit / 'p': Quiz / 't': Toggle content / Space: Multi-select / Arrow keys: Move \
+                 cursor",
+            )?;
+            layout.print(
+                &mut stdout,
+                0,
+                1,
+                "'h', 'j', 'k', 'l': Select hand / 'c': Toggle combo details / ':': Go to line / \
+                 '/': Search mixed hand / 'n', 'N': Next/prev match / 'e': Export",
+            )?;
+        }
+
+        let (num_rows, num_cols) =
+            solution.map_or((13, 13), |solution| solution.info.hand_layout.grid_size());
+        grid_cursor = (
+            grid_cursor.0.min(num_rows - 1),
+            grid_cursor.1.min(num_cols - 1),
+        );
+
+        let col_width = layout.col_width;
+        let (col_display_start, col_left) =
+            action_columns(indices.len(), files_width, col_width, layout.width as usize);
+        if col_display_start > 1 {
+            layout.print(&mut stdout, 0, layout.actions_top + 3, "(omitted)")?;
+        }
 
         if col_display_start == 1 {
-            for (i, (stack, _)) in outputs.iter().enumerate() {
+            for (i, output) in outputs.iter().enumerate() {
                 if i < files_top_index || files_top_index + 6 < i {
                     continue;
                 }
-                queue!(stdout, cursor::MoveTo(0, (i - files_top_index + 2) as u16))?;
+                let y = (i - files_top_index) as u16 + layout.actions_top;
                 if files_top_index > 0 && i == files_top_index {
-                    queue!(stdout, style::Print("    ^  "))?;
+                    layout.print(&mut stdout, 0, y, "    ^  ")?;
                     continue;
                 }
                 if i + 1 < outputs.len() && i == files_top_index + 6 {
-                    queue!(stdout, style::Print("    v  "))?;
+                    layout.print(&mut stdout, 0, y, "    v  ")?;
                     continue;
                 }
                 if i == indices[0] {
@@ -126,44 +501,49 @@ fn interactive_display(
                 if i == indices[0] && indices.len() == 1 {
                     queue!(stdout, style::SetAttribute(style::Attribute::Underlined))?;
                 }
-                queue!(
-                    stdout,
-                    style::Print(format!(
-                        "{} {:>3}bb",
+                layout.print(
+                    &mut stdout,
+                    0,
+                    y,
+                    &format!(
+                        "{} {}",
                         [' ', '*'][(i == indices[0]) as usize],
-                        stack
-                    )),
-                    style::SetAttribute(style::Attribute::Reset),
+                        output.label()
+                    ),
                 )?;
+                queue!(stdout, style::SetAttribute(style::Attribute::Reset))?;
             }
         }
 
-        let mut cur_rate = [vec![vec![1.0; 13]; 13], vec![vec![1.0; 13]; 13]];
+        let num_hands = solution.map_or(0, |solution| solution.info.hand_layout.num_hands());
+        let mut cur_combo_rate = [vec![1.0; num_hands], vec![1.0; num_hands]];
+        let mut prev_combo_rate = vec![1.0; num_hands];
 
         for i in 1..indices.len() {
+            let solution = solution.unwrap();
             let key = indices[1..i].iter().map(|x| *x as u8).collect::<Vec<_>>();
-            let strategy = &output.0[&key];
-            let avg_rate = compute_average_rate(&cur_rate[i % 2], &strategy);
+            let combo_strategy = &solution.combo_strategy[&key];
+            let avg_rate = compute_average_rate(&cur_combo_rate[i % 2], combo_strategy);
 
-            num_indices[i] = strategy.len();
+            num_indices[i] = combo_strategy.len();
 
             if col_display_start <= i {
                 let arrow_y = indices[i - 1] - [0, files_top_index][(i == 1) as usize];
-                queue!(
-                    stdout,
-                    cursor::MoveTo((23 * i - offset) as u16, arrow_y as u16 + 2),
-                    style::SetAttribute(style::Attribute::Bold),
-                    style::Print("=>"),
-                    style::SetAttribute(style::Attribute::Reset),
+                queue!(stdout, style::SetAttribute(style::Attribute::Bold))?;
+                layout.print(
+                    &mut stdout,
+                    (col_left + col_width * (i - col_display_start)) as u16,
+                    arrow_y as u16 + layout.actions_top,
+                    "=>",
                 )?;
+                queue!(stdout, style::SetAttribute(style::Attribute::Reset))?;
 
+                #[allow(clippy::needless_range_loop)]
                 for j in 0..num_indices[i] {
-                    let action = if j >= 2 && j + 1 == num_indices[i] {
-                        "All-in"
-                    } else if (i, j) == (2, 1) && indices[1] == 1 {
-                        "Check"
+                    let action = if layout.compact {
+                        short_action_label(solution, &indices, i, j)
                     } else {
-                        ["Fold", "Call", "Bet 2.5x", "Bet 3x", "Bet 3.5x", "Bet 4x"][j]
+                        action_label(solution, &indices, i, j)
                     };
                     if j == indices[i] {
                         queue!(stdout, style::SetAttribute(style::Attribute::Bold))?;
@@ -171,206 +551,341 @@ fn interactive_display(
                     if j == indices[i] && i + 1 == indices.len() {
                         queue!(stdout, style::SetAttribute(style::Attribute::Underlined))?;
                     }
-                    queue!(
-                        stdout,
-                        cursor::MoveTo((23 * i - offset + 2) as u16, j as u16 + 2),
-                        style::Print(format!(
-                            "{}{}{}[{:>5.2}%] {}",
-                            [' ', '(']
-                                [(i + 1 == indices.len() && multiple_select.contains(&j)) as usize],
-                            [' ', '*'][(j == indices[i]
-                                || (i + 1 == indices.len()
-                                    && multiple_select.contains(&indices[i])
-                                    && multiple_select.contains(&j)))
-                                as usize],
-                            [' ', ')']
-                                [(i + 1 == indices.len() && multiple_select.contains(&j)) as usize],
-                            100.0 * avg_rate[j],
+                    let is_selected = i + 1 == indices.len() && multiple_select.contains(&j);
+                    let is_marked = j == indices[i]
+                        || (i + 1 == indices.len()
+                            && multiple_select.contains(&indices[i])
+                            && multiple_select.contains(&j));
+                    let rate = if layout.compact {
+                        format!("{:>4.1}", 100.0 * avg_rate[j])
+                    } else {
+                        format!("{:>5.2}", 100.0 * avg_rate[j])
+                    };
+                    layout.print(
+                        &mut stdout,
+                        (col_left + col_width * (i - col_display_start) + 2) as u16,
+                        j as u16 + layout.actions_top,
+                        &format!(
+                            "{}{}{}[{}%] {}",
+                            [' ', '('][is_selected as usize],
+                            [' ', '*'][is_marked as usize],
+                            [' ', ')'][is_selected as usize],
+                            rate,
                             action
-                        )),
-                        style::SetAttribute(style::Attribute::Reset),
+                        ),
                     )?;
+                    queue!(stdout, style::SetAttribute(style::Attribute::Reset))?;
                 }
             }
 
+            if i + 1 == indices.len() {
+                prev_combo_rate = cur_combo_rate[i % 2].clone();
+            }
+
             if i + 1 == indices.len() && multiple_select.contains(&indices[i]) {
-                for j in 0..13 {
-                    for k in 0..13 {
-                        let mut tmp = 0.0;
-                        for idx in &multiple_select {
-                            tmp += strategy[*idx][j][k];
-                        }
-                        cur_rate[i % 2][j][k] *= tmp;
+                for k in 0..num_hands {
+                    let mut tmp = 0.0;
+                    for idx in &multiple_select {
+                        tmp += combo_strategy[*idx][k];
                     }
+                    cur_combo_rate[i % 2][k] *= tmp;
                 }
             } else {
-                for j in 0..13 {
-                    for k in 0..13 {
-                        cur_rate[i % 2][j][k] *= strategy[indices[i]][j][k];
-                    }
+                for k in 0..num_hands {
+                    cur_combo_rate[i % 2][k] *= combo_strategy[indices[i]][k];
                 }
             }
         }
 
+        let mut grid = None;
+
         if indices.len() >= 2 {
+            let solution = solution.unwrap();
+            let info = &solution.info;
+            let hand_layout = &info.hand_layout;
+            let (num_rows, num_cols) = hand_layout.grid_size();
             let player = (indices.len() - 1) % 2;
             let opponent = 1 - player;
-            let denom = calc_denom(&cur_rate[opponent]);
+            let combo_denom = hand_layout.denom(&cur_combo_rate[opponent]);
 
-            let mut ev = vec![vec![0.0; 13]; 13];
+            let mut combo_ev = vec![0.0; num_hands];
             let mut key = indices[1..].iter().map(|x| *x as u8).collect::<Vec<_>>();
             if multiple_select.contains(indices.last().unwrap()) {
                 for idx in &multiple_select {
                     *key.last_mut().unwrap() = *idx as u8;
-                    let tmp = &[ev1_table, ev0_table][player][&key];
-                    for j in 0..13 {
-                        for k in 0..13 {
-                            ev[j][k] += tmp[j][k];
-                        }
+                    let tmp = &solution.combo_ev[1 - player][&key];
+                    for k in 0..num_hands {
+                        combo_ev[k] += tmp[k];
                     }
                 }
             } else {
-                ev = [ev1_table, ev0_table][player][&key].clone();
+                combo_ev = solution.combo_ev[1 - player][&key].clone();
+            }
+
+            let mut combo_reach = cur_combo_rate[player].clone();
+            for k in 0..num_hands {
+                combo_reach[k] *= combo_denom[k];
             }
 
-            for j in 0..13 {
-                for k in 0..13 {
-                    ev[j][k] /= cur_rate[player][j][k] * denom[j][k];
+            let mut ev = hand_layout.summarize(&combo_ev, false);
+            let denom = hand_layout.summarize(&combo_reach, false);
+            for j in 0..num_rows {
+                for k in 0..num_cols {
+                    ev[j][k] /= denom[j][k];
                 }
             }
 
-            queue!(
-                stdout,
-                cursor::MoveTo(0, 10),
-                style::Print(" |   A     K     Q     J     T     9     8     7     6     5     4     3     2"),
-                cursor::MoveTo(0, 11),
-                style::Print("-+------------------------------------------------------------------------------"),
-            )?;
+            let rate = hand_layout.summarize(&cur_combo_rate[(indices.len() + 1) % 2], true);
+            let cells = (0..num_rows)
+                .map(|i| {
+                    (0..num_cols)
+                        .map(|j| grid_cell(display_mode, rate[i][j], ev[i][j]))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
 
-            for i in 0..13 {
-                let rank1 = 12 - i;
-                queue!(stdout, cursor::MoveTo(0, 12 + i as u16))?;
-                if i % 2 == 1 {
-                    queue!(
-                        stdout,
-                        style::SetBackgroundColor(style::Color::AnsiValue(235)),
-                    )?;
-                }
-                queue!(
-                    stdout,
-                    style::Print(format!(
-                        "{}|",
-                        ["2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K", "A"][rank1]
-                    ))
-                )?;
+            let unit = &info.unit;
+            let mut ev_line = format!(
+                "- EV: {:+.4}[{}] ({}) / {:+.4}[{}] ({})",
+                solution.ev[0],
+                unit,
+                info.player_names[0],
+                solution.ev[1],
+                unit,
+                info.player_names[1]
+            );
+            if !info.description.is_empty() {
+                ev_line.push_str(&format!(" [{}]", info.description));
+            }
+            let exploitability_line =
+                format!("- Exploitability: {:+.3e}[{}]", solution.output.2, unit);
 
-                for j in 0..13 {
-                    let rank2 = 12 - j;
-
-                    match display_mode {
-                        0 => {
-                            match (cur_rate[(indices.len() + 1) % 2][rank2][rank1] * 3.0 + 0.5)
-                                as usize
-                            {
-                                0 => queue!(
-                                    stdout,
-                                    style::SetForegroundColor(style::Color::Reset),
-                                    style::Print("   -  ")
-                                )?,
-                                1 => queue!(
-                                    stdout,
-                                    style::SetForegroundColor(style::Color::Yellow),
-                                    style::Print("   *  ")
-                                )?,
-                                2 => queue!(
-                                    stdout,
-                                    style::SetForegroundColor(style::Color::Cyan),
-                                    style::Print("  * * ")
-                                )?,
-                                3 => queue!(
-                                    stdout,
-                                    style::SetForegroundColor(style::Color::Green),
-                                    style::Print("  *** ")
-                                )?,
-                                _ => unreachable!(),
-                            }
-                        }
+            // keep the grid cursor visible when the grid does not fit in the terminal
+            if grid_cursor.0 < grid_scroll {
+                grid_scroll = grid_cursor.0;
+            }
+            if grid_cursor.0 >= grid_scroll + layout.grid_rows {
+                grid_scroll = grid_cursor.0 + 1 - layout.grid_rows;
+            }
 
-                        1 => {
-                            match (cur_rate[(indices.len() + 1) % 2][rank2][rank1] * 3.0 + 0.5)
-                                as usize
-                            {
-                                0 => queue!(
-                                    stdout,
-                                    style::SetForegroundColor(style::Color::Reset),
-                                    style::Print("   -  ")
-                                )?,
-                                _ => {
-                                    if ev[rank2][rank1] <= -10.0 {
-                                        queue!(
-                                            stdout,
-                                            style::SetForegroundColor(style::Color::Red),
-                                            style::Print(format!(" {:.1}", ev[rank2][rank1]))
-                                        )?
-                                    } else if ev[rank2][rank1] < 0.0 {
-                                        queue!(
-                                            stdout,
-                                            style::SetForegroundColor(style::Color::Magenta),
-                                            style::Print(format!(" {:.2}", ev[rank2][rank1]))
-                                        )?
-                                    } else if ev[rank2][rank1] < 10.0 {
-                                        queue!(
-                                            stdout,
-                                            style::SetForegroundColor(style::Color::Cyan),
-                                            style::Print(format!(" {:+.2}", ev[rank2][rank1]))
-                                        )?
-                                    } else {
-                                        queue!(
-                                            stdout,
-                                            style::SetForegroundColor(style::Color::Green),
-                                            style::Print(format!(" {:+.1}", ev[rank2][rank1]))
-                                        )?
-                                    }
-                                }
-                            }
-                        }
+            // the combo panel replaces the grid when there is no room beside it
+            let grid_width = layout.grid_width(num_cols);
+            let panel_width = 12 + layout.panel_col_width() * (num_indices[indices.len() - 1] + 1);
+            let panel_beside = grid_width + 2 + panel_width <= layout.width as usize;
+            let panel_x = [0, grid_width + 2][panel_beside as usize] as u16;
 
-                        _ => unreachable!(),
-                    }
-                }
+            let exported = viewer_export::Grid {
+                title: line_str(outputs, &indices),
+                subtitle: format!(
+                    "{} {}",
+                    info.player_names[indices.len() % 2],
+                    ["reach [%]".to_string(), format!("EV [{}]", unit)][display_mode]
+                ),
+                row_labels: hand_layout.row_labels(),
+                col_labels: hand_layout.col_labels(),
+                cells,
+                footer: vec![ev_line.clone(), exploitability_line.clone()],
+            };
 
-                queue!(
-                    stdout,
-                    style::SetForegroundColor(style::Color::Reset),
-                    style::SetBackgroundColor(style::Color::Reset),
-                )?;
+            if !show_combos || panel_beside {
+                draw_grid(&mut stdout, &layout, &exported, grid_cursor, grid_scroll)?;
             }
 
-            if terminal_height > 26 {
-                queue!(
-                    stdout,
-                    cursor::MoveTo(0, 26),
-                    style::Print(format!(
-                        "- EV: {:+.4}[bb] (SB) / {:+.4}[bb] (BB)",
-                        output.1, -output.1
-                    )),
+            if show_combos {
+                let key = indices[1..indices.len() - 1]
+                    .iter()
+                    .map(|x| *x as u8)
+                    .collect::<Vec<_>>();
+                let combo_strategy = &solution.combo_strategy[&key];
+                let combos = hand_layout.cell_hands(grid_cursor.0, grid_cursor.1);
+                let col = layout.panel_col_width();
+                let last = indices.len() - 1;
+
+                queue!(stdout, style::SetAttribute(style::Attribute::Bold))?;
+                layout.print(
+                    &mut stdout,
+                    panel_x,
+                    layout.grid_top,
+                    &format!(
+                        "{}{}{}",
+                        hand_layout.cell_str(grid_cursor.0, grid_cursor.1),
+                        if combos.len() > 1 {
+                            format!(" ({} combos)", combos.len())
+                        } else {
+                            String::new()
+                        },
+                        if solution.has_combo_data {
+                            ""
+                        } else {
+                            " - no suit data, restored from summary"
+                        }
+                    ),
                 )?;
+                queue!(stdout, style::SetAttribute(style::Attribute::Reset))?;
+
+                let mut header = "      Reach".to_string();
+                for j in 0..num_indices[last] {
+                    let label = short_action_label(solution, &indices, last, j);
+                    header.push_str(&format!("{:>width$}", label, width = col));
+                }
+                header.push_str(&format!("{:>width$}", "EV", width = col));
+                layout.print(&mut stdout, panel_x, layout.grid_top + 1, &header)?;
+
+                for (row, &k) in combos.iter().enumerate() {
+                    let y = layout.grid_top + 2 + row as u16;
+                    if y >= layout.info_top {
+                        break;
+                    }
+                    let mut line = format!(
+                        "{:<4} {:>5.1}%",
+                        hand_layout.hand_str(k),
+                        100.0 * prev_combo_rate[k]
+                    );
+                    #[allow(clippy::needless_range_loop)]
+                    for j in 0..num_indices[last] {
+                        if prev_combo_rate[k] == 0.0 {
+                            line.push_str(&format!("{:>width$}", "-", width = col));
+                        } else {
+                            let freq = format!("{:.1$}%", 100.0 * combo_strategy[j][k], col - 6);
+                            line.push_str(&format!("{:>width$}", freq, width = col));
+                        }
+                    }
+                    if combo_reach[k] == 0.0 {
+                        line.push_str(&format!("{:>width$}", "-", width = col));
+                    } else {
+                        let ev = format!("{:+.1$}", combo_ev[k] / combo_reach[k], col - 5);
+                        line.push_str(&format!("{:>width$}", ev, width = col));
+                    }
+                    layout.print(&mut stdout, panel_x, y, &line)?;
+                }
             }
 
-            if terminal_height > 27 {
-                queue!(
-                    stdout,
-                    cursor::MoveTo(0, 27),
-                    style::Print(format!("- Exploitability: {:+.3e}[bb]", output.2)),
-                )?;
+            if layout.compact {
+                let info_line = format!(
+                    "EV: {:+.3}/{:+.3}[{}] Expl: {:.2e}[{}]",
+                    solution.ev[0], solution.ev[1], unit, solution.output.2, unit
+                );
+                layout.print(&mut stdout, 0, layout.info_top, &info_line)?;
+            } else {
+                layout.print(&mut stdout, 0, layout.info_top, &ev_line)?;
+                layout.print(&mut stdout, 0, layout.info_top + 1, &exploitability_line)?;
             }
+
+            grid = Some(exported);
         }
 
+        let status = match &prompt {
+            Some((':', input)) => format!("Go to line: {}_", input),
+            Some(('/', input)) => format!("Search hand: {}_", input),
+            Some((_, input)) => format!("Export as (txt, csv or html): {}_", input),
+            None => message.clone(),
+        };
+        layout.print(&mut stdout, 0, layout.status_row, &status)?;
+
         // flush queue
         stdout.flush().unwrap();
 
         // read pressed key
+        let mut jump_to = None;
         match event::read()? {
+            // text input
+            Event::Key(key_ev) if prompt.is_some() => {
+                let (kind, input) = prompt.as_mut().unwrap();
+                match key_ev.code {
+                    KeyCode::Char(c) => input.push(c),
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Esc => prompt = None,
+                    KeyCode::Enter if *kind == ':' => {
+                        match parse_line(input, outputs, indices[0]) {
+                            Ok((line, notes)) => {
+                                message = format!("Line: {}", line_str(outputs, &line));
+                                if !notes.is_empty() {
+                                    message += &format!(" ({})", notes.join(", "));
+                                }
+                                jump_to = Some(line);
+                            }
+                            Err(err) => message = format!("Error: {}", err),
+                        }
+                        prompt = None;
+                    }
+                    KeyCode::Enter if *kind == 'e' => {
+                        match (viewer_export::Format::parse(input), &grid) {
+                            (Some(format), Some(grid)) => {
+                                match viewer_export::export(grid, &format, "export") {
+                                    Ok(path) => message = format!("Wrote '{}'", path.display()),
+                                    Err(err) => message = format!("Error: {}", err),
+                                }
+                            }
+                            (None, _) => message = format!("Error: unknown format '{}'", input),
+                            (_, None) => message = "Error: no grid to export".to_string(),
+                        }
+                        prompt = None;
+                    }
+                    KeyCode::Enter => {
+                        let hand_layout = match outputs[indices[0]].load() {
+                            Ok(loaded) => &loaded.info.hand_layout,
+                            Err(err) => {
+                                message = format!("Error: {}", err);
+                                prompt = None;
+                                continue;
+                            }
+                        };
+                        match hand_layout.parse_cell(input) {
+                            Some(cell) => {
+                                search_results = search_mixed(outputs, indices[0], cell);
+                                search_pos = 0;
+                                if search_results.is_empty() {
+                                    message = format!("'{}' does not mix anywhere", input.trim());
+                                } else {
+                                    message = format!(
+                                        "Match 1/{}: {}",
+                                        search_results.len(),
+                                        line_str(outputs, &search_results[0])
+                                    );
+                                    jump_to = Some(search_results[0].clone());
+                                    grid_cursor = cell;
+                                }
+                            }
+                            None => message = format!("Error: invalid hand '{}'", input.trim()),
+                        }
+                        prompt = None;
+                    }
+                    _ => (),
+                }
+            }
+
+            // open prompt
+            Event::Key(key_ev)
+                if key_ev == KeyCode::Char(':').into()
+                    || key_ev == KeyCode::Char('/').into()
+                    || key_ev == KeyCode::Char('e').into() =>
+            {
+                if let KeyCode::Char(c) = key_ev.code {
+                    prompt = Some((c, String::new()));
+                }
+            }
+
+            // next/previous match
+            Event::Key(key_ev)
+                if (key_ev == KeyCode::Char('n').into() || key_ev == KeyCode::Char('N').into())
+                    && !search_results.is_empty() =>
+            {
+                let len = search_results.len();
+                search_pos = match key_ev.code {
+                    KeyCode::Char('n') => (search_pos + 1) % len,
+                    _ => (search_pos + len - 1) % len,
+                };
+                message = format!(
+                    "Match {}/{}: {}",
+                    search_pos + 1,
+                    len,
+                    line_str(outputs, &search_results[search_pos])
+                );
+                jump_to = Some(search_results[search_pos].clone());
+            }
+
             // quit
             Event::Key(key_ev)
                 if key_ev == KeyCode::Char('q').into()
@@ -379,18 +894,28 @@ fn interactive_display(
                 break
             }
 
+            // start quiz
+            Event::Key(key_ev) if key_ev == KeyCode::Char('p').into() => {
+                let file_index = quiz_file(outputs, indices[0], &mut rng);
+                if !outputs[file_index].is_loaded() {
+                    show_loading(&mut stdout, &layout, &outputs[file_index])?;
+                }
+                match outputs[file_index].load() {
+                    Ok(_) => quiz = Some(sample_question(outputs, file_index, &mut rng)),
+                    Err(err) => message = format!("Error: {}", err),
+                }
+            }
+
             // toggle display
             Event::Key(key_ev) if key_ev == KeyCode::Char('t').into() => {
                 display_mode = (display_mode + 1) % 2;
             }
 
             // multiple select
-            Event::Key(key_ev) if key_ev == KeyCode::Char(' ').into() => {
-                if indices.len() >= 2 {
-                    let index = *indices.last().unwrap();
-                    if !multiple_select.insert(index) {
-                        multiple_select.remove(&index);
-                    }
+            Event::Key(key_ev) if key_ev == KeyCode::Char(' ').into() && indices.len() >= 2 => {
+                let index = *indices.last().unwrap();
+                if !multiple_select.insert(index) {
+                    multiple_select.remove(&index);
                 }
             }
 
@@ -398,6 +923,25 @@ fn interactive_display(
                 multiple_select.clear();
             }
 
+            // toggle combo details
+            Event::Key(key_ev) if key_ev == KeyCode::Char('c').into() => {
+                show_combos = !show_combos;
+            }
+
+            // move grid cursor
+            Event::Key(key_ev) if key_ev == KeyCode::Char('h').into() => {
+                grid_cursor.1 = (grid_cursor.1 + num_cols - 1) % num_cols;
+            }
+            Event::Key(key_ev) if key_ev == KeyCode::Char('j').into() => {
+                grid_cursor.0 = (grid_cursor.0 + 1) % num_rows;
+            }
+            Event::Key(key_ev) if key_ev == KeyCode::Char('k').into() => {
+                grid_cursor.0 = (grid_cursor.0 + num_rows - 1) % num_rows;
+            }
+            Event::Key(key_ev) if key_ev == KeyCode::Char('l').into() => {
+                grid_cursor.1 = (grid_cursor.1 + 1) % num_cols;
+            }
+
             // Up key
             Event::Key(key_ev)
                 if key_ev == KeyCode::Up.into() || key_ev == KeyCode::Char('w').into() =>
@@ -444,13 +988,21 @@ fn interactive_display(
                     || key_ev == KeyCode::Right.into()
                     || key_ev == KeyCode::Char('d').into() =>
             {
-                let last_idx = *indices.last().unwrap();
                 if indices.len() == 1 {
-                    output = &outputs[indices[0]].1 .0;
-                    ev0_table = &outputs[indices[0]].1 .1;
-                    ev1_table = &outputs[indices[0]].1 .2;
+                    if !outputs[indices[0]].is_loaded() {
+                        show_loading(&mut stdout, &layout, &outputs[indices[0]])?;
+                    }
+                    match outputs[indices[0]].load() {
+                        Ok(loaded) => solution = Some(loaded),
+                        Err(err) => {
+                            message = format!("Error: {}", err);
+                            continue;
+                        }
+                    }
                 }
-                if indices.len() == 1 || last_idx >= 2 || (indices.len() == 2 && last_idx == 1) {
+                // stop at terminal nodes
+                let key = indices[1..].iter().map(|x| *x as u8).collect::<Vec<_>>();
+                if solution.unwrap().combo_strategy.contains_key(&key) {
                     indices.push(0);
                     num_indices.push(0); // temporal value
                     multiple_select.clear();
@@ -459,20 +1011,35 @@ fn interactive_display(
 
             // Backspace or Left key
             Event::Key(key_ev)
-                if key_ev == KeyCode::Backspace.into()
+                if (key_ev == KeyCode::Backspace.into()
                     || key_ev == KeyCode::Left.into()
-                    || key_ev == KeyCode::Char('a').into() =>
+                    || key_ev == KeyCode::Char('a').into())
+                    && indices.len() > 1 =>
             {
-                if indices.len() > 1 {
-                    indices.pop();
-                    num_indices.pop();
-                    multiple_select.clear();
-                }
+                indices.pop();
+                num_indices.pop();
+                multiple_select.clear();
+            }
+
+            // terminal resized
+            Event::Resize(width, height) => {
+                layout = Layout::new(width, height);
             }
 
             // ignore other keys
             _ => (),
         }
+
+        if let Some(line) = jump_to {
+            files_top_index = line[0]
+                .saturating_sub(5)
+                .min(outputs.len().saturating_sub(7));
+            solution = Some(outputs[line[0]].solution());
+            num_indices = vec![0; line.len()]; // temporal values
+            num_indices[0] = outputs.len();
+            indices = line;
+            multiple_select.clear();
+        }
     }
 
     execute!(
@@ -486,112 +1053,962 @@ fn interactive_display(
     Ok(())
 }
 
-fn compute_average_rate(cur_rate: &Vec<Vec<f64>>, strategy: &Vec<Vec<Vec<f64>>>) -> Vec<f64> {
+/// Question of the quiz mode
+struct Question {
+    /// Line of the decision (the file index followed by the actions so far)
+    line: Vec<usize>,
+    /// Grid cell of the hand class
+    cell: (usize, usize),
+    /// Frequency of each action for the hand class (weighted by reach)
+    freqs: Vec<f64>,
+    /// EV of each action for the hand class
+    evs: Vec<f64>,
+    answer: Option<usize>,
+}
+
+/// Accuracy summary of the quiz mode over the session
+#[derive(Default)]
+struct QuizStats {
+    questions: usize,
+    /// Number of answers the solution plays at least `MIX_THRESHOLD` of the time
+    correct: usize,
+    freq_sum: f64,
+    ev_loss: f64,
+}
+
+/// Screen positions, which depend on the terminal size.
+struct Layout {
+    width: u16,
+    height: u16,
+    /// Whether the terminal is smaller than 80x28.
+    compact: bool,
+    /// Width of each action column.
+    col_width: usize,
+    /// Row where files and action columns start.
+    actions_top: u16,
+    /// Row of the grid header.
+    grid_top: u16,
+    /// Number of visible grid rows (the grid scrolls when some rows do not fit).
+    grid_rows: usize,
+    cell_width: usize,
+    /// Row of EV and exploitability.
+    info_top: u16,
+    /// Row of the prompt and messages.
+    status_row: u16,
+}
+
+impl Layout {
+    fn new(width: u16, height: u16) -> Self {
+        if width >= 80 && height >= 28 {
+            Self {
+                width,
+                height,
+                compact: false,
+                col_width: 23,
+                actions_top: 2,
+                grid_top: 10,
+                grid_rows: 13,
+                cell_width: 6,
+                info_top: 26,
+                status_row: 25,
+            }
+        } else {
+            // help (1 row), actions (7 rows), grid header (1 row), grid, info (1 row), status
+            let grid_rows = (height as usize).saturating_sub(11).clamp(1, 13);
+            Self {
+                width,
+                height,
+                compact: true,
+                col_width: 19,
+                actions_top: 1,
+                grid_top: 8,
+                grid_rows,
+                cell_width: 5,
+                info_top: 9 + grid_rows as u16,
+                status_row: 10 + grid_rows as u16,
+            }
+        }
+    }
+
+    fn grid_width(&self, num_cols: usize) -> usize {
+        2 + num_cols * self.cell_width
+    }
+
+    fn panel_col_width(&self) -> usize {
+        [8, 7][self.compact as usize]
+    }
+
+    /// Prints `text` at (`x`, `y`), clipping the part outside the terminal.
+    fn print(
+        &self,
+        stdout: &mut std::io::Stdout,
+        x: u16,
+        y: u16,
+        text: &str,
+    ) -> crossterm::Result<()> {
+        if x < self.width && y < self.height {
+            let text = text
+                .chars()
+                .take((self.width - x) as usize)
+                .collect::<String>();
+            queue!(stdout, cursor::MoveTo(x, y), style::Print(text))?;
+        }
+        Ok(())
+    }
+}
+
+fn show_loading(
+    stdout: &mut std::io::Stdout,
+    layout: &Layout,
+    output: &OutputFile,
+) -> crossterm::Result<()> {
+    queue!(
+        stdout,
+        cursor::MoveTo(0, layout.status_row),
+        terminal::Clear(terminal::ClearType::CurrentLine),
+    )?;
+    let text = format!("Loading '{}'. Please wait...", output.path.display());
+    layout.print(stdout, 0, layout.status_row, &text)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn draw_grid(
+    stdout: &mut std::io::Stdout,
+    layout: &Layout,
+    grid: &viewer_export::Grid,
+    grid_cursor: (usize, usize),
+    grid_scroll: usize,
+) -> crossterm::Result<()> {
+    let cell_width = layout.cell_width;
+    let num_cols = grid.col_labels.len();
+    let mut header = " |".to_string();
+    for label in &grid.col_labels {
+        let padding = " ".repeat(cell_width - 4);
+        header.push_str(&format!("{:>4}{}", label, padding));
+    }
+    layout.print(stdout, 0, layout.grid_top, header.trim_end())?;
+    if !layout.compact {
+        let separator = format!("-+{}", "-".repeat(layout.grid_width(num_cols) - 2));
+        layout.print(stdout, 0, layout.grid_top + 1, &separator)?;
+    }
+
+    let rows_top = layout.grid_top + 1 + (!layout.compact) as u16;
+    for i in grid_scroll..(grid_scroll + layout.grid_rows).min(grid.row_labels.len()) {
+        let y = rows_top + (i - grid_scroll) as u16;
+        if y >= layout.height || layout.width < 2 {
+            break;
+        }
+
+        queue!(stdout, cursor::MoveTo(0, y))?;
+        if i % 2 == 1 {
+            queue!(
+                stdout,
+                style::SetBackgroundColor(style::Color::AnsiValue(235)),
+            )?;
+        }
+        queue!(stdout, style::Print(format!("{}|", grid.row_labels[i])))?;
+
+        // number of cells fitting in the terminal
+        let num_cells = ((layout.width as usize - 2) / cell_width).min(num_cols);
+        #[allow(clippy::needless_range_loop)]
+        for j in 0..num_cells {
+            if (i, j) == grid_cursor {
+                queue!(stdout, style::SetAttribute(style::Attribute::Reverse))?;
+            }
+
+            let (text, value, color) = &grid.cells[i][j];
+            let text = if !layout.compact {
+                text.clone()
+            } else if text.contains(['*', '-']) && !text.contains('.') {
+                format!("{:>4} ", text.trim())
+            } else if value.abs() < 10.0 {
+                format!("{:>+4.1} ", value)
+            } else {
+                format!("{:>+4.0} ", value)
+            };
+            queue!(
+                stdout,
+                style::SetForegroundColor(*color),
+                style::Print(text)
+            )?;
+
+            if (i, j) == grid_cursor {
+                queue!(stdout, style::SetAttribute(style::Attribute::NoReverse))?;
+            }
+        }
+
+        queue!(
+            stdout,
+            style::SetForegroundColor(style::Color::Reset),
+            style::SetBackgroundColor(style::Color::Reset),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns the first action column to display and its x position. `num_columns` includes the file
+/// list, which is `files_width` wide. When the columns do not fit in `width`, the file list and the
+/// leftmost action columns are omitted, and the remaining columns start at x = 10.
+fn action_columns(
+    num_columns: usize,
+    files_width: usize,
+    col_width: usize,
+    width: usize,
+) -> (usize, usize) {
+    if num_columns <= 2 || files_width + col_width * (num_columns - 1) <= width {
+        return (1, files_width);
+    }
+    let fitting = width.saturating_sub(10) / col_width;
+    let start = num_columns
+        .saturating_sub(fitting)
+        .clamp(2, num_columns - 1);
+    (start, 10)
+}
+
+#[allow(clippy::needless_range_loop)]
+fn compute_average_rate(cur_rate: &[f64], strategy: &[Vec<f64>]) -> Vec<f64> {
     let num_actions = strategy.len();
     let mut ret = Vec::new();
     for action in 0..num_actions {
         let mut tmp = 0.0;
-        for i in 0..13 {
-            for j in 0..13 {
-                let count = [12.0, 4.0, 6.0][(i <= j) as usize + (i == j) as usize];
-                tmp += cur_rate[i][j] * strategy[action][i][j] * count;
-            }
+        for k in 0..cur_rate.len() {
+            tmp += cur_rate[k] * strategy[action][k];
         }
-        ret.push(tmp / (52. * 51. / 2.));
+        ret.push(tmp / cur_rate.len() as f64);
     }
     ret
 }
 
-fn restore_strategy(summarized: &Vec<Vec<Vec<f64>>>) -> Vec<Vec<f64>> {
-    let num_actions = summarized.len();
-    let mut strategy = vec![vec![0.0; 52 * 51 / 2]; num_actions];
+/// Computes the EV of each private information set of `player` at every node.
+fn calc_ev(strategy: &ComboType, player: usize, spec: &GameSpec) -> ComboEvType {
+    match *spec {
+        GameSpec::Preflop {
+            stacks,
+            blinds,
+            rake,
+            limp,
+        } => {
+            let node = game_preflop::PreflopNode::with_stacks(stacks, blinds)
+                .with_rake(rake)
+                .with_limp(limp);
+            calc_node_ev(&node, strategy, player)
+        }
+        GameSpec::PushFold {
+            stacks,
+            blinds,
+            rake,
+        } => {
+            let node = game_push_fold::PushFoldNode::with_stacks(stacks, blinds).with_rake(rake);
+            calc_node_ev(&node, strategy, player)
+        }
+        GameSpec::PushFoldPayoffs { payoffs } => {
+            let node = game_push_fold::PushFoldNode::with_payoffs(payoffs);
+            calc_node_ev(&node, strategy, player)
+        }
+        GameSpec::Kuhn => calc_node_ev(&game_kuhn::KuhnNode::new(), strategy, player),
+    }
+}
 
-    for action in 0..num_actions {
-        let mut k = 0;
-        for i in 0..51 {
-            for j in (i + 1)..52 {
-                if i % 4 == j % 4 {
-                    strategy[action][k] = summarized[action][i / 4][j / 4];
+fn calc_node_ev(node: &impl GameNode, strategy: &ComboType, player: usize) -> ComboEvType {
+    let ones = vec![1.0; node.private_info_set_len()];
+    let ev = std::sync::Mutex::new(HashMap::new());
+    cfr::compute_ev_detail(node, player, &ones, &ones, strategy, &ev);
+    ev.into_inner().unwrap()
+}
+
+/// Computes the value of each action at the decision `key` for each hand of `player`, given the
+/// opponent's reach `pmi`. Unlike the EV tables, actions the solution never plays also get values.
+fn calc_action_ev(
+    strategy: &ComboType,
+    player: usize,
+    spec: &GameSpec,
+    key: &[u8],
+    pmi: &[f64],
+) -> Vec<Vec<f64>> {
+    match *spec {
+        GameSpec::Preflop {
+            stacks,
+            blinds,
+            rake,
+            limp,
+        } => {
+            let node = game_preflop::PreflopNode::with_stacks(stacks, blinds)
+                .with_rake(rake)
+                .with_limp(limp);
+            cfr::compute_action_values(&node, player, key, pmi, strategy)
+        }
+        GameSpec::PushFold {
+            stacks,
+            blinds,
+            rake,
+        } => {
+            let node = game_push_fold::PushFoldNode::with_stacks(stacks, blinds).with_rake(rake);
+            cfr::compute_action_values(&node, player, key, pmi, strategy)
+        }
+        GameSpec::PushFoldPayoffs { payoffs } => {
+            let node = game_push_fold::PushFoldNode::with_payoffs(payoffs);
+            cfr::compute_action_values(&node, player, key, pmi, strategy)
+        }
+        GameSpec::Kuhn => {
+            cfr::compute_action_values(&game_kuhn::KuhnNode::new(), player, key, pmi, strategy)
+        }
+    }
+}
+
+fn calc_combo_denom(opp_rate: &[f64]) -> Vec<f64> {
+    let opp_rate_sum = opp_rate.iter().sum::<f64>();
+    let mut opp_rate_sum_ex = [0.0; 52];
+
+    let mut k = 0;
+    for i in 0..51 {
+        for j in (i + 1)..52 {
+            opp_rate_sum_ex[i] += opp_rate[k];
+            opp_rate_sum_ex[j] += opp_rate[k];
+            k += 1;
+        }
+    }
+
+    let prob = (2. * 2.) / (52. * 51. * 50. * 49.);
+    let mut k = 0;
+    let mut ret = Vec::with_capacity(52 * 51 / 2);
+    for i in 0..51 {
+        for j in (i + 1)..52 {
+            let sum = opp_rate_sum - opp_rate_sum_ex[i] - opp_rate_sum_ex[j] + opp_rate[k];
+            ret.push(prob * sum);
+            k += 1;
+        }
+    }
+
+    ret
+}
+
+/// Grid of the viewer for each hand layout. The cells of hold'em are shown in display order, i.e.,
+/// the first row and column correspond to aces, and suited hands are above the diagonal.
+impl HandLayout {
+    fn num_hands(&self) -> usize {
+        match self {
+            HandLayout::Holdem => 52 * 51 / 2,
+            HandLayout::Cards(cards) => cards.len(),
+        }
+    }
+
+    /// Returns the number of rows and columns of the grid.
+    fn grid_size(&self) -> (usize, usize) {
+        match self {
+            HandLayout::Holdem => (13, 13),
+            HandLayout::Cards(cards) => (1, cards.len()),
+        }
+    }
+
+    fn row_labels(&self) -> Vec<String> {
+        match self {
+            HandLayout::Holdem => (0..13).map(|i| RANKS[12 - i].to_string()).collect(),
+            HandLayout::Cards(_) => vec![" ".to_string()],
+        }
+    }
+
+    fn col_labels(&self) -> Vec<String> {
+        match self {
+            HandLayout::Holdem => self.row_labels(),
+            HandLayout::Cards(cards) => cards.clone(),
+        }
+    }
+
+    /// Returns the hands (combos or cards) in the cell at (`row`, `col`).
+    fn cell_hands(&self, row: usize, col: usize) -> Vec<usize> {
+        match self {
+            HandLayout::Holdem => class_combos(12 - col, 12 - row)
+                .iter()
+                .map(|&(c1, c2)| combo_index(c1, c2))
+                .collect(),
+            HandLayout::Cards(_) => vec![col],
+        }
+    }
+
+    /// Returns the name of the cell at (`row`, `col`), e.g., `A5s`.
+    fn cell_str(&self, row: usize, col: usize) -> String {
+        match self {
+            HandLayout::Holdem => class_str(12 - col, 12 - row),
+            HandLayout::Cards(cards) => cards[col].clone(),
+        }
+    }
+
+    /// Returns the name of the hand, e.g., `As5s`.
+    fn hand_str(&self, hand: usize) -> String {
+        match self {
+            HandLayout::Holdem => {
+                let (c1, c2) = preflop_equity::COMBO_CARDS[hand];
+                format!("{}{}", card_str(c2), card_str(c1))
+            }
+            HandLayout::Cards(cards) => cards[hand].clone(),
+        }
+    }
+
+    /// Sums (or averages if `average` is set) `values` of the hands in each cell.
+    fn summarize(&self, values: &[f64], average: bool) -> Vec<Vec<f64>> {
+        let (rows, cols) = self.grid_size();
+        (0..rows)
+            .map(|i| {
+                (0..cols)
+                    .map(|j| {
+                        let hands = self.cell_hands(i, j);
+                        let sum = hands.iter().map(|&k| values[k]).sum::<f64>();
+                        [sum, sum / hands.len() as f64][average as usize]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the probability that each hand meets the opponent's range `opp_rate`, excluding the
+    /// hands sharing a card.
+    fn denom(&self, opp_rate: &[f64]) -> Vec<f64> {
+        match self {
+            HandLayout::Holdem => calc_combo_denom(opp_rate),
+            HandLayout::Cards(cards) => {
+                let n = cards.len() as f64;
+                let opp_rate_sum = opp_rate.iter().sum::<f64>();
+                opp_rate
+                    .iter()
+                    .map(|rate| (opp_rate_sum - rate) / (n * (n - 1.0)))
+                    .collect()
+            }
+        }
+    }
+
+    /// Parses a cell such as `A5s`, `KQo` or `TT` (or a card name) and returns its position.
+    fn parse_cell(&self, input: &str) -> Option<(usize, usize)> {
+        match self {
+            HandLayout::Holdem => parse_hand_class(input).map(|class| (12 - class.1, 12 - class.0)),
+            HandLayout::Cards(cards) => cards
+                .iter()
+                .position(|card| card.eq_ignore_ascii_case(input.trim()))
+                .map(|col| (0, col)),
+        }
+    }
+
+    /// Deals a hand to each player at random.
+    fn deal(&self, rng: &mut Rng) -> [usize; 2] {
+        match self {
+            HandLayout::Holdem => {
+                let cards = cards::deal(rng, 4, &[]);
+                [
+                    combo_index(cards[0], cards[1]),
+                    combo_index(cards[2], cards[3]),
+                ]
+            }
+            HandLayout::Cards(cards) => {
+                let first = rng.below(cards.len());
+                let second = (first + 1 + rng.below(cards.len() - 1)) % cards.len();
+                [first, second]
+            }
+        }
+    }
+
+    /// Returns the position of the cell containing `hand`.
+    fn hand_cell(&self, hand: usize) -> (usize, usize) {
+        match self {
+            HandLayout::Holdem => {
+                let (c1, c2) = preflop_equity::COMBO_CARDS[hand];
+                let (lo, hi) = (c1 / 4, c2 / 4);
+                // suited hands are above the diagonal
+                if c1 % 4 == c2 % 4 {
+                    (12 - hi, 12 - lo)
                 } else {
-                    strategy[action][k] = summarized[action][j / 4][i / 4];
+                    (12 - lo, 12 - hi)
                 }
-                k += 1;
             }
+            HandLayout::Cards(_) => (0, hand),
         }
     }
 
-    strategy
+    /// Expands the summarized strategy of a node to hands.
+    fn restore_strategy(&self, summarized: &[Vec<Vec<f64>>]) -> Vec<Vec<f64>> {
+        match self {
+            HandLayout::Holdem => preflop_equity::restore_strategy(summarized),
+            HandLayout::Cards(_) => summarized.iter().map(|grid| grid.concat()).collect(),
+        }
+    }
 }
 
-fn summarize_ev(ev: &Vec<f64>) -> Vec<Vec<f64>> {
-    let mut summarized = vec![vec![0.0; 13]; 13];
+/// Returns the label of the `j`-th action after `line[1..i]`.
+fn action_label<'a>(solution: &'a Solution, line: &[usize], i: usize, j: usize) -> &'a str {
+    let key = line[1..i].iter().map(|x| *x as u8).collect::<Vec<_>>();
+    &solution.info.action_labels[&key][j]
+}
 
-    let mut k = 0;
-    for i in 0..51 {
-        for j in (i + 1)..52 {
-            if i % 4 == j % 4 {
-                summarized[i / 4][j / 4] += ev[k];
-            } else {
-                summarized[j / 4][i / 4] += ev[k];
+fn short_action_label<'a>(solution: &'a Solution, line: &[usize], i: usize, j: usize) -> &'a str {
+    action_label(solution, line, i, j).trim_start_matches("Bet ")
+}
+
+fn class_str(rank1: usize, rank2: usize) -> String {
+    match rank1.cmp(&rank2) {
+        std::cmp::Ordering::Equal => format!("{}{}", RANKS[rank1], RANKS[rank2]),
+        std::cmp::Ordering::Less => format!("{}{}s", RANKS[rank2], RANKS[rank1]),
+        std::cmp::Ordering::Greater => format!("{}{}o", RANKS[rank1], RANKS[rank2]),
+    }
+}
+
+/// Converts a line (e.g., `[file index, 2, 3, 5, 1]`) to a string like `40bb: R2.5 R3 R4 C`.
+fn line_str(outputs: &[OutputFile], line: &[usize]) -> String {
+    let solution = outputs[line[0]].solution();
+    let mut ret = format!("{}:", outputs[line[0]].name());
+    for i in 1..line.len() {
+        ret.push(' ');
+        ret.push_str(&action_token(action_label(solution, line, i, line[i])));
+    }
+    ret
+}
+
+/// Parses a line such as `40bb: R2.5 R3 R4 C` (or `10/25bb: ...` for unequal stacks,
+/// `40bb-ante0.1-rake5: ...` for non-default blinds and rake, `pf10bb: A C` for push/fold files
+/// and `kuhn: X B C` for Kuhn poker).
+/// When the file is omitted, the file at `file_index` is used. Raises of sizes not in a pre-flop
+/// tree (e.g., `R2.2`) are translated to the most likely action of the pseudo-harmonic mapping,
+/// which is noted in the returned strings.
+fn parse_line(
+    input: &str,
+    outputs: &[OutputFile],
+    file_index: usize,
+) -> Result<(Vec<usize>, Vec<String>), String> {
+    let (file, tokens) = split_line(input);
+
+    let file_index = match file {
+        Some(prefix) => match outputs.iter().position(|output| output.name() == prefix) {
+            Some(index) => index,
+            None => find_output(outputs, prefix)?,
+        },
+        None => file_index,
+    };
+
+    let solution = outputs[file_index].load().map_err(|err| err.to_string())?;
+    let key = |actions: &[usize]| actions.iter().map(|x| *x as u8).collect::<Vec<_>>();
+    let labels = |actions: &[usize]| solution.info.action_labels.get(&key(actions)).cloned();
+    let translate = |actions: &[usize], token: &str| {
+        let labels = labels(actions)?;
+        let mix = action_translation::parse_raise(token)
+            .and_then(|multiple| translate_raise(&solution.info.spec, &key(actions), multiple))?;
+        let (action, _) = mix
+            .iter()
+            .cloned()
+            .max_by_key(|&(_, prob)| NotNan::new(prob).unwrap())
+            .unwrap();
+        let mix_str = action_translation::mix_str(&mix, &labels);
+        Some((action, format!("{} => {}", token, mix_str)))
+    };
+
+    let (actions, notes) = parse_actions(&tokens, labels, translate)?;
+    let mut line = vec![file_index];
+    line.extend(actions);
+
+    // make the last decision visible
+    let key = line[1..].iter().map(|x| *x as u8).collect::<Vec<_>>();
+    if solution.combo_strategy.contains_key(&key) {
+        line.push(0);
+    }
+
+    Ok((line, notes))
+}
+
+/// Translates a raise to `multiple` times the current bet at the decision `key` of a pre-flop
+/// game (`None` for other games).
+fn translate_raise(spec: &GameSpec, key: &[u8], multiple: f64) -> Option<Vec<(usize, f64)>> {
+    match *spec {
+        GameSpec::Preflop {
+            stacks,
+            blinds,
+            rake,
+            limp,
+        } => {
+            let mut node = game_preflop::PreflopNode::with_stacks(stacks, blinds)
+                .with_rake(rake)
+                .with_limp(limp);
+            for &action in key {
+                node = node.play(action as usize);
             }
-            k += 1;
+            Some(action_translation::translate_multiple(&node, multiple))
         }
+        _ => None,
     }
+}
 
-    summarized
+/// Finds the file of stacks and tags written differently from the file name, e.g., `40.0bb`.
+fn find_output(outputs: &[OutputFile], prefix: &str) -> Result<usize, String> {
+    let (game, prefix_rest) = match prefix.strip_prefix("pf") {
+        Some(rest) => ("push_fold", rest),
+        None => ("preflop", prefix),
+    };
+    let (stacks, tag) = parse_file(prefix_rest)?;
+    let limp_pos = ["-nolimp", "-limpraise", "-reopen"]
+        .iter()
+        .filter_map(|limp_tag| tag.find(limp_tag))
+        .min()
+        .unwrap_or(tag.len());
+    let (tag, limp_tag) = tag.split_at(limp_pos);
+    let (tag, rake_tag) = match tag.find("-rake") {
+        Some(pos) => (&tag[..pos], &tag[pos..]),
+        None => (tag, ""),
+    };
+    let blinds = Blinds::from_tag(tag).ok_or(format!("invalid blinds '{}'", tag))?;
+    let rake = Rake::from_tag(rake_tag).ok_or(format!("invalid rake '{}'", rake_tag))?;
+    let limp = Limp::from_tag(limp_tag).ok_or(format!("invalid limp options '{}'", limp_tag))?;
+    outputs
+        .iter()
+        .position(|output| {
+            output.game == game
+                && output.stacks.map(|stacks| stacks.map(NotNan::into_inner)) == Some(stacks)
+                && output.blinds == blinds
+                && Rake::from_tag(&output.rake_tag) == Some(rake)
+                && Limp::from_tag(&output.limp_tag) == Some(limp)
+        })
+        .ok_or(format!("no file for {}", prefix))
 }
 
-fn calc_ev(
-    summarized_strategy: &HashMap<PublicInfoSet, Vec<Vec<Vec<f64>>>>,
-    player: usize,
-    stack: f64,
-) -> HashMap<PublicInfoSet, Vec<Vec<f64>>> {
-    let strategy = summarized_strategy
+/// Finds the decisions where the hands in the grid cell `cell` play more than one action.
+fn search_mixed(
+    outputs: &[OutputFile],
+    file_index: usize,
+    cell: (usize, usize),
+) -> Vec<Vec<usize>> {
+    let solution = outputs[file_index].solution();
+    let strategy = &solution.combo_strategy;
+    let combos = solution.info.hand_layout.cell_hands(cell.0, cell.1);
+
+    let mut keys = strategy.keys().collect::<Vec<_>>();
+    keys.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+
+    let mut ret = Vec::new();
+    for key in keys {
+        let player = key.len() % 2;
+        let mut reach = vec![1.0; combos.len()];
+        for p in (player..key.len()).step_by(2) {
+            let prev = &strategy[&key[..p].to_vec()];
+            for (r, &k) in reach.iter_mut().zip(&combos) {
+                *r *= prev[key[p] as usize][k];
+            }
+        }
+
+        let reach_sum = reach.iter().sum::<f64>();
+        if reach_sum < MIX_THRESHOLD * combos.len() as f64 {
+            continue;
+        }
+
+        let value = &strategy[key];
+        let freq = value
+            .iter()
+            .map(|action| {
+                combos
+                    .iter()
+                    .zip(&reach)
+                    .map(|(&k, r)| action[k] * r)
+                    .sum::<f64>()
+            })
+            .map(|x| x / reach_sum)
+            .collect::<Vec<_>>();
+
+        if freq.iter().filter(|&&x| x >= MIX_THRESHOLD).count() >= 2 {
+            let mut line = vec![file_index];
+            line.extend(key.iter().map(|x| *x as usize));
+            let best = (0..freq.len()).max_by(|&a, &b| freq[a].total_cmp(&freq[b]));
+            line.push(best.unwrap());
+            ret.push(line);
+        }
+    }
+
+    ret
+}
+
+/// Picks a file of the same game as the file at `file_index` at random, e.g., a random stack depth
+/// of pre-flop files.
+fn quiz_file(outputs: &[OutputFile], file_index: usize, rng: &mut Rng) -> usize {
+    let candidates = (0..outputs.len())
+        .filter(|&i| outputs[i].game == outputs[file_index].game)
+        .collect::<Vec<_>>();
+    candidates[rng.below(candidates.len())]
+}
+
+/// Picks a decision and a hand class at random: hands are dealt and played according to the
+/// solution, and one of the decisions on the way is asked, so that both are weighted by reach.
+fn sample_question(outputs: &[OutputFile], file_index: usize, rng: &mut Rng) -> Question {
+    let solution = outputs[file_index].solution();
+    let strategy = &solution.combo_strategy;
+    let hand_layout = &solution.info.hand_layout;
+    let hands = hand_layout.deal(rng);
+
+    let mut key = Vec::new();
+    let mut decisions = Vec::new();
+    while let Some(value) = strategy.get(&key) {
+        decisions.push(key.clone());
+        let hand = hands[key.len() % 2];
+        let weights = value.iter().map(|action| action[hand]).collect::<Vec<_>>();
+        key.push(rng.choose_weighted(&weights) as u8);
+    }
+    let key = decisions.swap_remove(rng.below(decisions.len()));
+    let player = key.len() % 2;
+
+    let num_hands = hand_layout.num_hands();
+    let mut reach = [vec![1.0; num_hands], vec![1.0; num_hands]];
+    for i in 0..key.len() {
+        let prev = &strategy[&key[..i].to_vec()][key[i] as usize];
+        for k in 0..num_hands {
+            reach[i % 2][k] *= prev[k];
+        }
+    }
+
+    let cell = hand_layout.hand_cell(hands[player]);
+    let combos = hand_layout.cell_hands(cell.0, cell.1);
+    let reach_sum = combos.iter().map(|&k| reach[player][k]).sum::<f64>();
+    let freqs = strategy[&key]
         .iter()
-        .map(|(key, value)| (key.clone(), restore_strategy(value)))
-        .collect::<HashMap<_, _>>();
-    let node = game_preflop::PreflopNode::new(stack);
-    let ones = vec![1.0; 52 * 51 / 2];
-    let ev = std::sync::Mutex::new(HashMap::new());
-    cfr::compute_ev_detail(&node, player, &ones, &ones, &strategy, &ev);
-    let ev = ev
-        .lock()
-        .unwrap()
+        .map(|action| {
+            combos
+                .iter()
+                .map(|&k| reach[player][k] * action[k])
+                .sum::<f64>()
+                / reach_sum
+        })
+        .collect();
+
+    let opp_reach = &reach[player ^ 1];
+    let values = calc_action_ev(strategy, player, &solution.info.spec, &key, opp_reach);
+    let denom = hand_layout.denom(opp_reach);
+    let denom_sum = combos
+        .iter()
+        .map(|&k| reach[player][k] * denom[k])
+        .sum::<f64>();
+    let evs = values
         .iter()
-        .map(|(key, value)| (key.clone(), summarize_ev(value)))
+        .map(|value| {
+            combos
+                .iter()
+                .map(|&k| reach[player][k] * value[k])
+                .sum::<f64>()
+                / denom_sum
+        })
         .collect();
-    ev
+
+    let mut line = vec![file_index];
+    line.extend(key.iter().map(|x| *x as usize));
+    Question {
+        line,
+        cell,
+        freqs,
+        evs,
+        answer: None,
+    }
 }
 
-fn calc_denom(opp_rate: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
-    let mut ret = vec![vec![0.0; 13]; 13];
+impl QuizStats {
+    fn record(&mut self, question: &Question, answer: usize) {
+        let best_ev = question
+            .evs
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+        self.questions += 1;
+        self.correct += (question.freqs[answer] >= MIX_THRESHOLD) as usize;
+        self.freq_sum += question.freqs[answer];
+        self.ev_loss += best_ev - question.evs[answer];
+    }
 
-    for i in 0..51 {
-        for j in (i + 1)..52 {
-            let rate = if i % 4 == j % 4 {
-                opp_rate[i / 4][j / 4]
-            } else {
-                opp_rate[j / 4][i / 4]
-            };
-            for m in 0..51 {
-                for n in (m + 1)..52 {
-                    if i == m || i == n || j == m || j == n {
-                        continue;
-                    } else if m % 4 == n % 4 {
-                        ret[m / 4][n / 4] += rate;
-                    } else {
-                        ret[n / 4][m / 4] += rate;
-                    }
-                }
-            }
+    /// Returns the summary, e.g., `5 questions, 4 correct (80.0%), avg. frequency 62.3%, ...`.
+    fn summary(&self, unit: &str) -> String {
+        let n = self.questions.max(1) as f64;
+        format!(
+            "{} questions, {} correct ({:.1}%), avg. frequency {:.1}%, EV loss {:.3}[{}] \
+             ({:.3}[{}] per question)",
+            self.questions,
+            self.correct,
+            100.0 * self.correct as f64 / n,
+            100.0 * self.freq_sum / n,
+            self.ev_loss,
+            unit,
+            self.ev_loss / n,
+            unit
+        )
+    }
+}
+
+fn draw_quiz(
+    stdout: &mut std::io::Stdout,
+    layout: &Layout,
+    outputs: &[OutputFile],
+    question: &Question,
+    stats: &QuizStats,
+) -> crossterm::Result<()> {
+    let solution = outputs[question.line[0]].solution();
+    let info = &solution.info;
+    let player = (question.line.len() - 1) % 2;
+    let num_actions = question.freqs.len();
+    let cell_str = info.hand_layout.cell_str(question.cell.0, question.cell.1);
+
+    layout.print(
+        stdout,
+        0,
+        0,
+        "Quiz mode / 1-9: Answer / Enter: Next question / Esc: Exit quiz",
+    )?;
+    queue!(stdout, style::SetAttribute(style::Attribute::Bold))?;
+    layout.print(
+        stdout,
+        0,
+        2,
+        &format!(
+            "Question #{}",
+            stats.questions + question.answer.is_none() as usize
+        ),
+    )?;
+    queue!(stdout, style::SetAttribute(style::Attribute::Reset))?;
+    layout.print(
+        stdout,
+        0,
+        3,
+        &format!("Line: {}", line_str(outputs, &question.line)),
+    )?;
+    layout.print(
+        stdout,
+        0,
+        4,
+        &format!("{} to act with {}", info.player_names[player], cell_str),
+    )?;
+
+    for j in 0..num_actions {
+        let label = action_label(solution, &question.line, question.line.len(), j);
+        let mut text = format!("{}: {:<10}", j + 1, label);
+        if question.answer.is_some() {
+            text.push_str(&format!(
+                " {:>5.1}%  EV {:+.3}[{}]",
+                100.0 * question.freqs[j],
+                question.evs[j],
+                info.unit
+            ));
+        }
+        if question.answer == Some(j) {
+            text.push_str("  <= your answer");
         }
+        layout.print(stdout, 2, 6 + j as u16, &text)?;
     }
 
-    for i in 0..13 {
-        for j in 0..13 {
-            ret[i][j] *= (2. * 2.) / (52. * 51. * 50. * 49.);
+    let y = 7 + num_actions as u16;
+    if let Some(answer) = question.answer {
+        let best_ev = question
+            .evs
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let verdict = if question.freqs[answer] >= MIX_THRESHOLD {
+            "Correct"
+        } else {
+            "Mistake"
+        };
+        layout.print(
+            stdout,
+            0,
+            y,
+            &format!(
+                "{}: the solution plays it {:.1}% of the time (EV loss {:.3}[{}])",
+                verdict,
+                100.0 * question.freqs[answer],
+                best_ev - question.evs[answer],
+                info.unit
+            ),
+        )?;
+    }
+    if stats.questions > 0 {
+        let summary = format!("Session: {}", stats.summary(&info.unit));
+        layout.print(stdout, 0, y + 2, &summary)?;
+    }
+    Ok(())
+}
+
+/// Returns the text and color of a grid cell.
+fn grid_cell(display_mode: usize, rate: f64, ev: f64) -> (String, f64, style::Color) {
+    let level = (rate * 3.0 + 0.5) as usize;
+    match (display_mode, level) {
+        (0, 0) => ("   -  ".to_string(), 100.0 * rate, style::Color::Reset),
+        (0, 1) => ("   *  ".to_string(), 100.0 * rate, style::Color::Yellow),
+        (0, 2) => ("  * * ".to_string(), 100.0 * rate, style::Color::Cyan),
+        (0, 3) => ("  *** ".to_string(), 100.0 * rate, style::Color::Green),
+        (1, 0) => ("   -  ".to_string(), f64::NAN, style::Color::Reset),
+        (1, _) if ev <= -10.0 => (format!(" {:.1}", ev), ev, style::Color::Red),
+        (1, _) if ev < 0.0 => (format!(" {:.2}", ev), ev, style::Color::Magenta),
+        (1, _) if ev < 10.0 => (format!(" {:+.2}", ev), ev, style::Color::Cyan),
+        (1, _) => (format!(" {:+.1}", ev), ev, style::Color::Green),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_fit() {
+        assert_eq!(action_columns(1, 10, 23, 80), (1, 10));
+        assert_eq!(action_columns(4, 10, 23, 80), (1, 10));
+        // a file label longer than a column
+        assert_eq!(action_columns(3, 30, 19, 80), (1, 30));
+    }
+
+    #[test]
+    fn columns_omitted() {
+        // 10 + 5 * 23 > 80, so only the last three columns are shown
+        assert_eq!(action_columns(6, 10, 23, 80), (3, 10));
+        // a long file label: columns 3 and 4 are shown at x = 10 and 29
+        assert_eq!(action_columns(5, 40, 19, 60), (3, 10));
+        // a terminal narrower than the margin still shows the last column
+        assert_eq!(action_columns(5, 40, 19, 5), (4, 10));
+    }
+
+    fn output(game: &'static str, stacks: Option<[f64; 2]>, setting: &str) -> OutputFile {
+        let (tag, limp_tag) = setting.split_at(setting.find("-limp").unwrap_or(setting.len()));
+        let (blinds, rake_tag) = match tag.find("-rake") {
+            Some(pos) => (&tag[..pos], &tag[pos..]),
+            None => (tag, ""),
+        };
+        OutputFile {
+            game,
+            stacks: stacks.map(|stacks| stacks.map(|stack| NotNan::new(stack).unwrap())),
+            blinds: Blinds::from_tag(blinds).unwrap_or_default(),
+            rake_tag: rake_tag.to_string(),
+            limp_tag: limp_tag.to_string(),
+            setting: setting.to_string(),
+            path: PathBuf::new(),
+            solution: OnceCell::new(),
         }
     }
 
-    ret
+    #[test]
+    fn find_outputs() {
+        let outputs = [
+            output("preflop", Some([40.0, 40.0]), ""),
+            output("preflop", Some([10.0, 25.0]), "-ante0.1-rake5-cap3"),
+            output("push_fold", Some([10.0, 10.0]), ""),
+            output("kuhn", None, ""),
+            output("preflop", Some([40.0, 40.0]), "-limpraise0.5_1"),
+        ];
+        assert_eq!(outputs[1].name(), "10/25bb-ante0.1-rake5-cap3");
+        assert_eq!(find_output(&outputs, "40.0bb"), Ok(0));
+        assert_eq!(
+            find_output(&outputs, "10/25bb-ante0.10-rake5.0-cap3"),
+            Ok(1)
+        );
+        assert_eq!(find_output(&outputs, "pf10"), Ok(2));
+        assert_eq!(
+            find_output(&outputs, "10/25bb-ante0.1"),
+            Err("no file for 10/25bb-ante0.1".to_string())
+        );
+        assert!(find_output(&outputs, "kuhn2").is_err());
+        assert_eq!(find_output(&outputs, "40bb-limpraise0.50_1.0"), Ok(4));
+        assert_eq!(
+            find_output(&outputs, "40bb-nolimp"),
+            Err("no file for 40bb-nolimp".to_string())
+        );
+        assert_eq!(
+            find_output(&outputs, "40bb-limpraise1_0.5"),
+            Err("invalid limp options '-limpraise1_0.5'".to_string())
+        );
+    }
 }
@@ -0,0 +1,381 @@
+use crate::showdown::evaluate_fold;
+use bincode::deserialize;
+use once_cell::sync::{Lazy, OnceCell};
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::Read;
+
+/// Number of combos (pairs of hole cards).
+pub const NUM_COMBOS: usize = 52 * 51 / 2;
+
+/// Number of suit-isomorphic hand classes (13 pairs, 78 suited and 78 offsuit hands).
+pub const NUM_CLASSES: usize = 13 * 13;
+
+/// Probability of each (ordered) deal of two disjoint combos.
+pub const DEAL_PROB: f64 = (2. * 2.) / (52. * 51. * 50. * 49.);
+
+/// Path of the equity table, which can be overridden by `set_equity_path` before `EQUITY_TABLE` is
+/// first accessed.
+static EQUITY_PATH: OnceCell<String> = OnceCell::new();
+
+/// Default path of the equity table.
+pub const DEFAULT_EQUITY_PATH: &str = "static/heads_up_pre_flop_equity.bin";
+
+/// Overrides the path of the equity table (e.g., to use a generated fixture). Has no effect once the
+/// path has been set or the table has been loaded.
+pub fn set_equity_path(path: &str) {
+    let _ = EQUITY_PATH.set(path.to_string());
+}
+
+/// Heads-up pre-flop equity: `EQUITY_TABLE[k1 * NUM_COMBOS + k2]` is the number of boards where
+/// combo `k1` wins against combo `k2`, counted twice, plus the number of boards where they tie.
+pub static EQUITY_TABLE: Lazy<Vec<u32>> = Lazy::new(|| {
+    let path = EQUITY_PATH.get_or_init(|| DEFAULT_EQUITY_PATH.to_string());
+    let mut infile = File::open(path).unwrap_or_else(|_| panic!("could not open '{}'", path));
+    let mut buf = Vec::new();
+    infile.read_to_end(&mut buf).unwrap();
+    deserialize::<Vec<u32>>(&buf).unwrap()
+});
+
+/// Denominator of `EQUITY_TABLE` (twice the number of possible boards).
+pub const EQUITY_TOTAL: f64 = 2. * (48. * 47. * 46. * 45. * 44.) / (5. * 4. * 3. * 2.);
+
+/// Class-vs-class tables: (sum of deal probabilities, sum of deal probabilities times the
+/// showdown payoff per unit bet) over all disjoint combo pairs of the two classes.
+pub static CLASS_TABLES: Lazy<(Vec<f64>, Vec<f64>)> = Lazy::new(|| {
+    let mut count = vec![0.0; NUM_CLASSES * NUM_CLASSES];
+    let mut showdown = vec![0.0; NUM_CLASSES * NUM_CLASSES];
+    let classes = combo_classes();
+
+    let mut k = 0;
+    for k1 in 0..NUM_COMBOS {
+        let (i, j) = COMBO_CARDS[k1];
+        for k2 in 0..NUM_COMBOS {
+            let (m, n) = COMBO_CARDS[k2];
+            if i != m && i != n && j != m && j != n {
+                let eq = EQUITY_TABLE[k] as f64 / EQUITY_TOTAL;
+                let index = classes[k1] * NUM_CLASSES + classes[k2];
+                count[index] += DEAL_PROB;
+                showdown[index] += DEAL_PROB * (2.0 * eq - 1.0);
+            }
+            k += 1;
+        }
+    }
+
+    (count, showdown)
+});
+
+/// Computes counterfactual values of a heads-up pre-flop terminal node where the player receives
+/// `payoff + showdown_payoff * (2 * equity - 1)`, over hand classes if `isomorphic` and over combos
+/// otherwise. A fold is represented by `showdown_payoff == 0`.
+pub fn evaluate_terminal(
+    pmi: &[f64],
+    isomorphic: bool,
+    payoff: f64,
+    showdown_payoff: f64,
+) -> Vec<f64> {
+    if isomorphic {
+        let (count, showdown) = &*CLASS_TABLES;
+        return count
+            .chunks(NUM_CLASSES)
+            .zip(showdown.chunks(NUM_CLASSES))
+            .map(|(count_row, showdown_row)| {
+                let mut cfvalue = 0.0;
+                for ((c, s), p) in count_row.iter().zip(showdown_row).zip(pmi) {
+                    cfvalue += (payoff * c + showdown_payoff * s) * p;
+                }
+                cfvalue
+            })
+            .collect();
+    }
+
+    if showdown_payoff == 0.0 {
+        return evaluate_fold(pmi, 0, payoff * DEAL_PROB);
+    }
+
+    let mut ret = evaluate_showdown(pmi, showdown_payoff);
+    if payoff != 0.0 {
+        let base = evaluate_fold(pmi, 0, payoff * DEAL_PROB);
+        ret.iter_mut().zip(base).for_each(|(r, b)| *r += b);
+    }
+    ret
+}
+
+/// Computes counterfactual values of a heads-up pre-flop all-in showdown over combos, where the
+/// winner receives `payoff` from the loser and `pmi` is the opponent's reach probability of each
+/// combo. The loop is split across threads by the first card of the player's combo.
+pub fn evaluate_showdown(pmi: &[f64], payoff: f64) -> Vec<f64> {
+    (0usize..51)
+        .into_par_iter()
+        .map(|i| {
+            let mut k = ((103 - i) * i / 2) * NUM_COMBOS;
+            let mut ret = Vec::with_capacity(51 - i);
+            for j in (i + 1)..52 {
+                let k_start = k;
+                let mut cfvalue = 0.0;
+                for m in 0..51 {
+                    for n in (m + 1)..52 {
+                        if i == m || i == n || j == m || j == n {
+                            k += 1;
+                            continue;
+                        }
+                        let eq = EQUITY_TABLE[k] as f64 / EQUITY_TOTAL;
+                        let eq_minus = 1.0 - eq;
+                        cfvalue += (eq - eq_minus) * pmi[k - k_start];
+                        k += 1;
+                    }
+                }
+                ret.push(payoff * DEAL_PROB * cfvalue);
+            }
+            ret
+        })
+        .flatten()
+        .collect()
+}
+
+/// Cards of each combo in ascending order.
+pub static COMBO_CARDS: Lazy<Vec<(usize, usize)>> = Lazy::new(|| {
+    let mut ret = Vec::with_capacity(NUM_COMBOS);
+    for i in 0..51 {
+        for j in (i + 1)..52 {
+            ret.push((i, j));
+        }
+    }
+    ret
+});
+
+/// Returns the hand class of the combo consisting of `card1` and `card2` (`card1 < card2`).
+/// The class index is `13 * row + col` of the summarized 13x13 grid, i.e., suited hands are in
+/// the upper triangle and offsuit hands are in the lower triangle.
+#[inline]
+pub fn class_index(card1: usize, card2: usize) -> usize {
+    if card1 % 4 == card2 % 4 {
+        13 * (card1 / 4) + card2 / 4
+    } else {
+        13 * (card2 / 4) + card1 / 4
+    }
+}
+
+/// Returns the hand class of each combo.
+pub fn combo_classes() -> Vec<usize> {
+    COMBO_CARDS
+        .iter()
+        .map(|&(i, j)| class_index(i, j))
+        .collect()
+}
+
+/// Returns the number of combos of each hand class.
+pub fn class_weights() -> Vec<f64> {
+    let mut ret = vec![0.0; NUM_CLASSES];
+    for class in combo_classes() {
+        ret[class] += 1.0;
+    }
+    ret
+}
+
+/// Expands class-level vectors (e.g., strategy of each action) to combo-level vectors.
+pub fn expand_classes(values: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let classes = combo_classes();
+    values
+        .iter()
+        .map(|value| classes.iter().map(|&class| value[class]).collect())
+        .collect()
+}
+
+/// Averages combo-level vectors over the combos of each hand class.
+pub fn average_classes(values: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let classes = combo_classes();
+    let weights = class_weights();
+    values
+        .iter()
+        .map(|value| {
+            let mut ret = vec![0.0; NUM_CLASSES];
+            for (&class, v) in classes.iter().zip(value) {
+                ret[class] += v / weights[class];
+            }
+            ret
+        })
+        .collect()
+}
+
+/// Arranges class-level vectors into the summarized 13x13 grids.
+pub fn summarize_classes(values: &[Vec<f64>]) -> Vec<Vec<Vec<f64>>> {
+    values
+        .iter()
+        .map(|value| value.chunks(13).map(|row| row.to_vec()).collect())
+        .collect()
+}
+
+/// Restores a combo-level strategy from the summarized 13x13 grid of each action.
+pub fn restore_strategy(summarized: &[Vec<Vec<f64>>]) -> Vec<Vec<f64>> {
+    let flattened = summarized
+        .iter()
+        .map(|grid| grid.concat())
+        .collect::<Vec<_>>();
+    expand_classes(&flattened)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use bincode::serialize;
+    use std::sync::Once;
+
+    /// Points `EQUITY_TABLE` to a synthetic table with pseudo-random equities, where the equities
+    /// of two combos against each other sum to one. The equity only depends on the hand classes of
+    /// the two combos, so that the table is suit-isomorphic like the real one.
+    pub fn use_fixture() {
+        static FIXTURE: Once = Once::new();
+        FIXTURE.call_once(|| {
+            let total = EQUITY_TOTAL as u32;
+            let classes = combo_classes();
+            let class_count = |lo: usize, hi: usize| {
+                let mut state = (lo * NUM_CLASSES + hi) as u64;
+                for _ in 0..2 {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                }
+                (state >> 33) as u32 % (total + 1)
+            };
+
+            let mut table = vec![0; NUM_COMBOS * NUM_COMBOS];
+            for k1 in 0..NUM_COMBOS {
+                let (i, j) = COMBO_CARDS[k1];
+                for k2 in (k1 + 1)..NUM_COMBOS {
+                    let (m, n) = COMBO_CARDS[k2];
+                    if i != m && i != n && j != m && j != n {
+                        let (c1, c2) = (classes[k1], classes[k2]);
+                        let count = match c1.cmp(&c2) {
+                            std::cmp::Ordering::Equal => total / 2,
+                            std::cmp::Ordering::Less => class_count(c1, c2),
+                            std::cmp::Ordering::Greater => total - class_count(c2, c1),
+                        };
+                        table[k1 * NUM_COMBOS + k2] = count;
+                        table[k2 * NUM_COMBOS + k1] = total - count;
+                    }
+                }
+            }
+
+            // the content is deterministic, so test binaries running concurrently may share it
+            let path = std::env::temp_dir().join("poker-cfr-test-equity.bin");
+            let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+            std::fs::write(&tmp_path, serialize(&table).unwrap()).unwrap();
+            std::fs::rename(&tmp_path, &path).unwrap();
+            set_equity_path(path.to_str().unwrap());
+        });
+    }
+
+    /// Returns deterministic pseudo-random reach probabilities of length `len`, some of which are
+    /// zero.
+    pub fn reach(seed: u64, len: usize) -> Vec<f64> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let x = (state >> 11) as f64 / (1u64 << 53) as f64;
+                if x < 0.1 {
+                    0.0
+                } else {
+                    x
+                }
+            })
+            .collect()
+    }
+
+    /// Computes the combo-level values by comparing every pair of combos, where the player
+    /// receives `win` when winning the showdown and `lose` when losing it.
+    pub fn evaluate_pairwise(pmi: &[f64], win: f64, lose: f64) -> Vec<f64> {
+        let mut ret = vec![0.0; NUM_COMBOS];
+        for k1 in 0..NUM_COMBOS {
+            let (i, j) = COMBO_CARDS[k1];
+            for k2 in 0..NUM_COMBOS {
+                let (m, n) = COMBO_CARDS[k2];
+                if i != m && i != n && j != m && j != n {
+                    let eq = EQUITY_TABLE[k1 * NUM_COMBOS + k2] as f64 / EQUITY_TOTAL;
+                    ret[k1] += DEAL_PROB * pmi[k2] * (eq * win + (1.0 - eq) * lose);
+                }
+            }
+        }
+        ret
+    }
+
+    pub fn assert_close(lhs: &[f64], rhs: &[f64]) {
+        assert_eq!(lhs.len(), rhs.len());
+        for (k, (l, r)) in lhs.iter().zip(rhs).enumerate() {
+            assert!((l - r).abs() < 1e-12, "hand {}: {} != {}", k, l, r);
+        }
+    }
+
+    /// Sums combo-level values over the combos of each hand class.
+    fn sum_classes(values: &[f64]) -> Vec<f64> {
+        let mut ret = vec![0.0; NUM_CLASSES];
+        for (class, v) in combo_classes().into_iter().zip(values) {
+            ret[class] += v;
+        }
+        ret
+    }
+
+    #[test]
+    fn showdown_matches_pairwise() {
+        use_fixture();
+        let pmi = reach(1, NUM_COMBOS);
+        assert_close(
+            &evaluate_showdown(&pmi, 2.5),
+            &evaluate_pairwise(&pmi, 2.5, -2.5),
+        );
+        assert_close(
+            &evaluate_terminal(&pmi, false, -0.25, 7.0),
+            &evaluate_pairwise(&pmi, 6.75, -7.25),
+        );
+    }
+
+    #[test]
+    fn fold_matches_pairwise() {
+        use_fixture();
+        let pmi = reach(2, NUM_COMBOS);
+        assert_close(
+            &evaluate_terminal(&pmi, false, -1.5, 0.0),
+            &evaluate_pairwise(&pmi, -1.5, -1.5),
+        );
+    }
+
+    #[test]
+    fn card_removal() {
+        // the opponent only holds combos containing the ace of spades
+        use_fixture();
+        let mut pmi = reach(3, NUM_COMBOS);
+        for (p, &(i, j)) in pmi.iter_mut().zip(COMBO_CARDS.iter()) {
+            if i != 51 && j != 51 {
+                *p = 0.0;
+            }
+        }
+
+        for (payoff, showdown_payoff) in [(1.0, 0.0), (0.5, 3.0)].iter() {
+            let values = evaluate_terminal(&pmi, false, *payoff, *showdown_payoff);
+            let expected =
+                evaluate_pairwise(&pmi, payoff + showdown_payoff, payoff - showdown_payoff);
+            assert_close(&values, &expected);
+            for (v, &(i, j)) in values.iter().zip(COMBO_CARDS.iter()) {
+                let blocked = v.abs() < 1e-12;
+                assert_eq!(blocked, i == 51 || j == 51, "combo ({}, {}): {}", i, j, v);
+            }
+        }
+    }
+
+    #[test]
+    fn classes_match_combos() {
+        use_fixture();
+        let class_pmi = reach(6, NUM_CLASSES);
+        let pmi = &expand_classes(std::slice::from_ref(&class_pmi))[0];
+        for (payoff, showdown_payoff) in [(-1.5, 0.0), (-0.3, 9.2)].iter() {
+            let combo_values = evaluate_terminal(pmi, false, *payoff, *showdown_payoff);
+            assert_close(
+                &evaluate_terminal(&class_pmi, true, *payoff, *showdown_payoff),
+                &sum_classes(&combo_values),
+            );
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Rake taken from the pot by the house. The winner pays `percent`% of the whole pot (including
+/// the dead money), up to `cap` in bb. With `no_flop_no_drop`, hands ending before the flop (i.e.,
+/// by a fold) are not raked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Rake {
+    pub percent: f64,
+    pub cap: Option<f64>,
+    pub no_flop_no_drop: bool,
+}
+
+impl Rake {
+    /// Returns whether no rake is taken at all.
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.percent == 0.0 || self.cap == Some(0.0)
+    }
+
+    /// Returns the rake taken from `pot`. `saw_flop` is false when the hand ended by a fold.
+    #[inline]
+    pub fn amount(&self, pot: f64, saw_flop: bool) -> f64 {
+        if self.no_flop_no_drop && !saw_flop {
+            return 0.0;
+        }
+        let ret = pot * self.percent / 100.0;
+        match self.cap {
+            Some(cap) => ret.min(cap),
+            None => ret,
+        }
+    }
+
+    /// Returns the tag appended to output file names (empty when no rake is taken).
+    pub fn tag(&self) -> String {
+        if self.is_zero() {
+            return String::new();
+        }
+        let mut ret = format!("-rake{}", self.percent);
+        if let Some(cap) = self.cap {
+            ret.push_str(&format!("-cap{}", cap));
+        }
+        if self.no_flop_no_drop {
+            ret.push_str("-nfnd");
+        }
+        ret
+    }
+
+    /// Parses a tag generated by `tag`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        if tag.is_empty() {
+            return Some(Self::default());
+        }
+        let re = Regex::new(r"^-rake(\d+\.?\d*)(?:-cap(\d+\.?\d*))?(-nfnd)?$").unwrap();
+        let cs = re.captures(tag)?;
+        let cap = match cs.get(2) {
+            Some(cap) => Some(cap.as_str().parse().ok()?),
+            None => None,
+        };
+        Some(Self {
+            percent: cs[1].parse().ok()?,
+            cap,
+            no_flop_no_drop: cs.get(3).is_some(),
+        })
+    }
+
+    /// Returns a human-readable description (empty when no rake is taken).
+    pub fn description(&self) -> String {
+        if self.is_zero() {
+            return String::new();
+        }
+        let mut ret = format!("rake {}%", self.percent);
+        if let Some(cap) = self.cap {
+            ret.push_str(&format!(" capped at {}bb", cap));
+        }
+        if self.no_flop_no_drop {
+            ret.push_str(", no flop no drop");
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_round_trip() {
+        let cases = [
+            Rake::default(),
+            Rake {
+                percent: 5.0,
+                cap: None,
+                no_flop_no_drop: false,
+            },
+            Rake {
+                percent: 4.5,
+                cap: Some(3.0),
+                no_flop_no_drop: false,
+            },
+            Rake {
+                percent: 5.0,
+                cap: None,
+                no_flop_no_drop: true,
+            },
+            Rake {
+                percent: 10.0,
+                cap: Some(0.75),
+                no_flop_no_drop: true,
+            },
+        ];
+        for rake in cases.iter() {
+            assert_eq!(Rake::from_tag(&rake.tag()), Some(*rake));
+        }
+        assert_eq!(cases[0].tag(), "");
+        assert_eq!(cases[4].tag(), "-rake10-cap0.75-nfnd");
+    }
+
+    #[test]
+    fn zero_rake() {
+        // no rake is taken, so the tag is empty whatever the other fields are
+        for rake in [
+            Rake {
+                percent: 0.0,
+                cap: Some(3.0),
+                no_flop_no_drop: true,
+            },
+            Rake {
+                percent: 5.0,
+                cap: Some(0.0),
+                no_flop_no_drop: false,
+            },
+        ]
+        .iter()
+        {
+            assert!(rake.is_zero());
+            assert_eq!(rake.tag(), "");
+            assert_eq!(rake.description(), "");
+            assert_eq!(rake.amount(100.0, true), 0.0);
+        }
+    }
+
+    #[test]
+    fn invalid_tags() {
+        assert_eq!(Rake::from_tag("-rake"), None);
+        assert_eq!(Rake::from_tag("-cap3"), None);
+        assert_eq!(Rake::from_tag("-rake5-nfnd-cap3"), None);
+        assert_eq!(Rake::from_tag("rake5"), None);
+    }
+
+    #[test]
+    fn amount_and_description() {
+        let rake = Rake {
+            percent: 5.0,
+            cap: Some(3.0),
+            no_flop_no_drop: true,
+        };
+        assert!((rake.amount(20.0, true) - 1.0).abs() < 1e-12);
+        assert_eq!(rake.amount(100.0, true), 3.0);
+        assert_eq!(rake.amount(20.0, false), 0.0);
+        assert_eq!(rake.description(), "rake 5% capped at 3bb, no flop no drop");
+        let rake = Rake {
+            no_flop_no_drop: false,
+            cap: None,
+            ..rake
+        };
+        assert_eq!(rake.amount(20.0, false), 1.0);
+        assert_eq!(rake.description(), "rake 5%");
+    }
+}
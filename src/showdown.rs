@@ -0,0 +1,494 @@
+use crate::preflop_equity::NUM_COMBOS;
+use std::iter::once;
+
+/// Showdown evaluator for a fixed board. Cards are indexed by `4 * rank + suit` and combos are
+/// indexed in the lexicographic order of their (ascending) cards, as in the pre-flop games.
+///
+/// Counterfactual values are computed in O(n) by sweeping over the combos sorted by hand strength
+/// and removing the reach of the opponent's combos that share a card by inclusion-exclusion.
+/// No game node deals a board yet, so this is a standalone utility for post-flop games.
+pub struct ShowdownEvaluator {
+    /// (hand strength, combo index, card1, card2) of combos not conflicting with the board,
+    /// sorted by hand strength in ascending order
+    hands: Vec<(u32, usize, usize, usize)>,
+    board_mask: u64,
+}
+
+impl ShowdownEvaluator {
+    pub fn new(board: &[usize]) -> Self {
+        let board_mask = board.iter().fold(0u64, |acc, &c| acc | 1 << c);
+        let mut hands = Vec::new();
+        let mut cards = board.to_vec();
+        cards.extend_from_slice(&[0, 0]);
+
+        let mut k = 0;
+        for i in 0..51 {
+            for j in (i + 1)..52 {
+                if board_mask & (1 << i | 1 << j) == 0 {
+                    cards[board.len()] = i;
+                    cards[board.len() + 1] = j;
+                    hands.push((hand_strength(&cards), k, i, j));
+                }
+                k += 1;
+            }
+        }
+
+        hands.sort_unstable();
+        Self { hands, board_mask }
+    }
+
+    /// Computes counterfactual values of a showdown where the winner receives `payoff` from the
+    /// loser. `pmi` is the opponent's reach probability of each combo; chance probabilities are
+    /// not applied, so `payoff` should be scaled accordingly.
+    pub fn evaluate(&self, pmi: &[f64], payoff: f64) -> Vec<f64> {
+        let mut ret = vec![0.0; NUM_COMBOS];
+
+        // weaker hands
+        let mut pmi_sum = 0.0;
+        let mut pmi_sum_ex = [0.0; 52];
+        let mut begin = 0;
+        while begin < self.hands.len() {
+            let end = self.group_end(begin);
+            for &(_, k, i, j) in &self.hands[begin..end] {
+                ret[k] += payoff * (pmi_sum - pmi_sum_ex[i] - pmi_sum_ex[j]);
+            }
+            for &(_, k, i, j) in &self.hands[begin..end] {
+                pmi_sum += pmi[k];
+                pmi_sum_ex[i] += pmi[k];
+                pmi_sum_ex[j] += pmi[k];
+            }
+            begin = end;
+        }
+
+        // stronger hands
+        let mut pmi_sum = 0.0;
+        let mut pmi_sum_ex = [0.0; 52];
+        let mut end = self.hands.len();
+        while end > 0 {
+            let begin = self.group_begin(end);
+            for &(_, k, i, j) in &self.hands[begin..end] {
+                ret[k] -= payoff * (pmi_sum - pmi_sum_ex[i] - pmi_sum_ex[j]);
+            }
+            for &(_, k, i, j) in &self.hands[begin..end] {
+                pmi_sum += pmi[k];
+                pmi_sum_ex[i] += pmi[k];
+                pmi_sum_ex[j] += pmi[k];
+            }
+            end = begin;
+        }
+
+        ret
+    }
+
+    /// Computes counterfactual values when someone folded and the player receives `payoff`.
+    pub fn evaluate_fold(&self, pmi: &[f64], payoff: f64) -> Vec<f64> {
+        evaluate_fold(pmi, self.board_mask, payoff)
+    }
+
+    /// Returns the end of the group of equally strong hands starting at `begin`.
+    fn group_end(&self, begin: usize) -> usize {
+        let strength = self.hands[begin].0;
+        let len = self.hands[begin..]
+            .iter()
+            .take_while(|hand| hand.0 == strength)
+            .count();
+        begin + len
+    }
+
+    /// Returns the beginning of the group of equally strong hands ending at `end`.
+    fn group_begin(&self, end: usize) -> usize {
+        let strength = self.hands[end - 1].0;
+        let len = self.hands[..end]
+            .iter()
+            .rev()
+            .take_while(|hand| hand.0 == strength)
+            .count();
+        end - len
+    }
+}
+
+/// Computes counterfactual values when someone folded and the player receives `payoff`, ignoring
+/// combos that conflict with the dead cards in `board_mask` (bit `c` is set for card `c`).
+pub fn evaluate_fold(pmi: &[f64], board_mask: u64, payoff: f64) -> Vec<f64> {
+    let mut pmi_sum = 0.0;
+    let mut pmi_sum_ex = [0.0; 52];
+
+    let mut k = 0;
+    for i in 0..51 {
+        for j in (i + 1)..52 {
+            if board_mask & (1 << i | 1 << j) == 0 {
+                pmi_sum += pmi[k];
+                pmi_sum_ex[i] += pmi[k];
+                pmi_sum_ex[j] += pmi[k];
+            }
+            k += 1;
+        }
+    }
+
+    let mut k = 0;
+    let mut ret = Vec::with_capacity(NUM_COMBOS);
+    for i in 0..51 {
+        for j in (i + 1)..52 {
+            if board_mask & (1 << i | 1 << j) == 0 {
+                ret.push(payoff * (pmi_sum - pmi_sum_ex[i] - pmi_sum_ex[j] + pmi[k]));
+            } else {
+                ret.push(0.0);
+            }
+            k += 1;
+        }
+    }
+
+    ret
+}
+
+/// Returns the strength of the best five-card hand made from `cards` (larger is stronger). The
+/// category (0 => high card, ..., 8 => straight flush) is stored in the upper bits and the ranks
+/// deciding ties follow in descending order of significance. Does not allocate, since it is called
+/// for every combo of every board.
+pub fn hand_strength(cards: &[usize]) -> u32 {
+    let mut rank_count = [0u8; 13];
+    let mut suit_mask = [0u16; 4];
+    let mut rank_mask = 0u16;
+    for &card in cards {
+        rank_count[card / 4] += 1;
+        suit_mask[card % 4] |= 1 << (card / 4);
+        rank_mask |= 1 << (card / 4);
+    }
+
+    let flush_mask = suit_mask
+        .iter()
+        .copied()
+        .find(|mask| mask.count_ones() >= 5);
+
+    if let Some(high) = flush_mask.and_then(straight_high) {
+        return pack(8, [high]);
+    }
+
+    // ranks appearing exactly four, three and two times
+    let mut masks = [0u16; 5];
+    for (rank, &count) in rank_count.iter().enumerate() {
+        masks[count as usize] |= 1 << rank;
+    }
+    let [_, _, pairs, trips, quads] = masks;
+    let without = |rank: usize| rank_mask & !(1 << rank);
+
+    if quads != 0 {
+        let quad = highest(quads);
+        return pack(7, once(quad).chain(ranks(without(quad)).take(1)));
+    }
+
+    if trips != 0 {
+        let trip = highest(trips);
+        let rest = trips & !(1 << trip) | pairs;
+        if rest != 0 {
+            return pack(6, [trip, highest(rest)]);
+        }
+    }
+
+    if let Some(mask) = flush_mask {
+        return pack(5, ranks(mask).take(5));
+    }
+
+    if let Some(high) = straight_high(rank_mask) {
+        return pack(4, [high]);
+    }
+
+    if trips != 0 {
+        let trip = highest(trips);
+        return pack(3, once(trip).chain(ranks(without(trip)).take(2)));
+    }
+
+    match pairs.count_ones() {
+        0 => pack(0, ranks(rank_mask).take(5)),
+        1 => {
+            let pair = highest(pairs);
+            pack(1, once(pair).chain(ranks(without(pair)).take(3)))
+        }
+        _ => {
+            let high = highest(pairs);
+            let low = highest(pairs & !(1 << high));
+            let kicker = ranks(rank_mask & !(1 << high | 1 << low)).take(1);
+            pack(2, once(high).chain(once(low)).chain(kicker))
+        }
+    }
+}
+
+/// Returns the highest rank of a straight in `mask` (the five-high straight returns 3).
+fn straight_high(mask: u16) -> Option<usize> {
+    // treat ace as the lowest rank as well
+    let extended = (mask as u32) << 1 | (mask as u32) >> 12 & 1;
+    (4..14)
+        .rev()
+        .find(|&high| extended >> (high - 4) & 0x1f == 0x1f)
+        .map(|high| high - 1)
+}
+
+/// Returns the highest rank in `mask`, which must not be empty.
+#[inline]
+fn highest(mask: u16) -> usize {
+    15 - mask.leading_zeros() as usize
+}
+
+/// Returns the ranks in `mask` in descending order.
+#[inline]
+fn ranks(mask: u16) -> impl Iterator<Item = usize> {
+    (0..13).rev().filter(move |rank| mask & 1 << rank != 0)
+}
+
+#[inline]
+fn pack(category: u32, ranks: impl IntoIterator<Item = usize>) -> u32 {
+    ranks
+        .into_iter()
+        .enumerate()
+        .fold(category << 20, |acc, (i, rank)| {
+            acc | ((rank as u32 + 1) << (16 - 4 * i))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the card of `rank` (0 => deuce, ..., 12 => ace) and `suit`.
+    fn card(rank: usize, suit: usize) -> usize {
+        4 * rank + suit
+    }
+
+    /// Returns (combo index, card1, card2) of every combo.
+    fn combos() -> Vec<(usize, usize, usize)> {
+        let mut ret = Vec::with_capacity(NUM_COMBOS);
+        for i in 0..51 {
+            for j in (i + 1)..52 {
+                ret.push((ret.len(), i, j));
+            }
+        }
+        ret
+    }
+
+    /// Returns deterministic pseudo-random reach probabilities, some of which are zero.
+    fn reach(seed: u64) -> Vec<f64> {
+        let mut state = seed;
+        (0..NUM_COMBOS)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let x = (state >> 11) as f64 / (1u64 << 53) as f64;
+                if x < 0.1 {
+                    0.0
+                } else {
+                    x
+                }
+            })
+            .collect()
+    }
+
+    /// Computes the showdown values by comparing every pair of combos.
+    fn evaluate_pairwise(board: &[usize], pmi: &[f64], payoff: f64) -> Vec<f64> {
+        let mask = board.iter().fold(0u64, |acc, &c| acc | 1 << c);
+        // combos conflicting with the board are skipped below
+        let strengths = combos()
+            .iter()
+            .map(|&(_, i, j)| match mask & (1 << i | 1 << j) {
+                0 => hand_strength(&[board, &[i, j][..]].concat()),
+                _ => 0,
+            })
+            .collect::<Vec<_>>();
+        let mut ret = vec![0.0; NUM_COMBOS];
+        for (k1, i1, j1) in combos() {
+            if mask & (1 << i1 | 1 << j1) != 0 {
+                continue;
+            }
+            for (k2, i2, j2) in combos() {
+                let dead = mask | 1 << i1 | 1 << j1;
+                if dead & (1 << i2 | 1 << j2) != 0 {
+                    continue;
+                }
+                let sign = strengths[k1].cmp(&strengths[k2]) as i32;
+                ret[k1] += payoff * pmi[k2] * sign as f64;
+            }
+        }
+        ret
+    }
+
+    fn assert_close(lhs: &[f64], rhs: &[f64]) {
+        for (k, (l, r)) in lhs.iter().zip(rhs).enumerate() {
+            assert!((l - r).abs() < 1e-9, "combo {}: {} != {}", k, l, r);
+        }
+    }
+
+    #[test]
+    fn evaluate_matches_pairwise() {
+        let boards = [
+            // dry board
+            vec![card(12, 0), card(7, 1), card(2, 2), card(9, 3), card(0, 0)],
+            // paired board with flush and straight draws
+            vec![card(8, 0), card(8, 1), card(9, 0), card(10, 0), card(3, 2)],
+            // broadway on board, so many hands tie
+            vec![
+                card(12, 0),
+                card(11, 1),
+                card(10, 2),
+                card(9, 3),
+                card(8, 0),
+            ],
+            // four of a kind on board, so the kicker decides
+            vec![card(5, 0), card(5, 1), card(5, 2), card(5, 3), card(1, 0)],
+        ];
+        for (seed, board) in boards.iter().enumerate() {
+            let pmi = reach(seed as u64 + 1);
+            let evaluator = ShowdownEvaluator::new(board);
+            let expected = evaluate_pairwise(board, &pmi, 1.5);
+            assert_close(&evaluator.evaluate(&pmi, 1.5), &expected);
+        }
+    }
+
+    #[test]
+    fn evaluate_fold_matches_pairwise() {
+        let board = [card(12, 0), card(7, 1), card(2, 2)];
+        let mask = board.iter().fold(0u64, |acc, &c| acc | 1 << c);
+        let pmi = reach(42);
+        let mut expected = vec![0.0; NUM_COMBOS];
+        for (k1, i1, j1) in combos() {
+            if mask & (1 << i1 | 1 << j1) != 0 {
+                continue;
+            }
+            for (k2, i2, j2) in combos() {
+                let dead = mask | 1 << i1 | 1 << j1;
+                if dead & (1 << i2 | 1 << j2) == 0 {
+                    expected[k1] += -2.0 * pmi[k2];
+                }
+            }
+        }
+        assert_close(&evaluate_fold(&pmi, mask, -2.0), &expected);
+        assert_close(
+            &ShowdownEvaluator::new(&board).evaluate_fold(&pmi, -2.0),
+            &expected,
+        );
+    }
+
+    #[test]
+    fn hand_strength_order() {
+        let hands = [
+            // high card, pair, two pair, trips
+            vec![
+                card(12, 0),
+                card(10, 1),
+                card(7, 2),
+                card(4, 3),
+                card(2, 0),
+                card(1, 1),
+                card(0, 2),
+            ],
+            vec![
+                card(0, 0),
+                card(0, 1),
+                card(12, 2),
+                card(10, 3),
+                card(7, 0),
+                card(4, 1),
+                card(2, 2),
+            ],
+            vec![
+                card(1, 0),
+                card(1, 1),
+                card(0, 2),
+                card(0, 3),
+                card(12, 0),
+                card(10, 1),
+                card(7, 2),
+            ],
+            vec![
+                card(0, 0),
+                card(0, 1),
+                card(0, 2),
+                card(12, 3),
+                card(10, 0),
+                card(7, 1),
+                card(4, 2),
+            ],
+            // wheel, six-high straight
+            vec![
+                card(12, 0),
+                card(0, 1),
+                card(1, 2),
+                card(2, 3),
+                card(3, 0),
+                card(9, 1),
+                card(10, 2),
+            ],
+            vec![
+                card(0, 0),
+                card(1, 1),
+                card(2, 2),
+                card(3, 3),
+                card(4, 0),
+                card(9, 1),
+                card(10, 2),
+            ],
+            // flush, full house, quads, straight flush (wheel)
+            vec![
+                card(0, 0),
+                card(2, 0),
+                card(4, 0),
+                card(6, 0),
+                card(8, 0),
+                card(9, 1),
+                card(10, 2),
+            ],
+            vec![
+                card(0, 0),
+                card(0, 1),
+                card(0, 2),
+                card(1, 3),
+                card(1, 0),
+                card(12, 1),
+                card(11, 2),
+            ],
+            vec![
+                card(0, 0),
+                card(0, 1),
+                card(0, 2),
+                card(0, 3),
+                card(1, 0),
+                card(12, 1),
+                card(11, 2),
+            ],
+            vec![
+                card(12, 0),
+                card(0, 0),
+                card(1, 0),
+                card(2, 0),
+                card(3, 0),
+                card(11, 1),
+                card(11, 2),
+            ],
+        ];
+        let strengths = hands
+            .iter()
+            .map(|hand| hand_strength(hand))
+            .collect::<Vec<_>>();
+        assert!(strengths.windows(2).all(|w| w[0] < w[1]), "{:?}", strengths);
+
+        // the best five cards decide, so the sixth and seventh cards do not matter
+        let a = [
+            card(12, 0),
+            card(12, 1),
+            card(11, 0),
+            card(10, 1),
+            card(9, 2),
+            card(1, 3),
+            card(0, 0),
+        ];
+        let b = [
+            card(12, 2),
+            card(12, 3),
+            card(11, 1),
+            card(10, 2),
+            card(9, 3),
+            card(2, 3),
+            card(1, 0),
+        ];
+        assert_eq!(hand_strength(&a), hand_strength(&b));
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs::File;
+use std::io::{Result, Write};
+
+/// Result of solving one stack depth in a sweep.
+pub struct SweepEntry {
+    pub stack: f64,
+    pub iterations: usize,
+    /// EV of (SB, BB)
+    pub ev: [f64; 2],
+    pub exploitability: f64,
+    pub seconds: f64,
+    pub path: String,
+}
+
+/// Parses a comma-separated list of stacks. Each element is either a stack (e.g., `15`) or an
+/// inclusive range with a step (e.g., `20..50:10`, which is the same as `20,30,40,50`).
+pub fn parse_stacks(input: &str) -> std::result::Result<Vec<f64>, String> {
+    let parse = |s: &str| {
+        s.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|&value| value > 0.0)
+            .ok_or(format!("invalid stack '{}'", s))
+    };
+
+    let mut ret = Vec::new();
+    for item in input.split(',') {
+        match item.find("..") {
+            Some(pos) => {
+                let (start, rest) = (&item[..pos], &item[pos + 2..]);
+                let (end, step) = match rest.find(':') {
+                    Some(pos) => (&rest[..pos], parse(&rest[pos + 1..])?),
+                    None => return Err(format!("range '{}' needs a step (e.g., 10..50:5)", item)),
+                };
+                let (start, end) = (parse(start)?, parse(end)?);
+                let mut i = 0;
+                // avoid accumulating rounding errors of the step
+                while start + i as f64 * step <= end + 1e-9 {
+                    ret.push(start + i as f64 * step);
+                    i += 1;
+                }
+            }
+            None => ret.push(parse(item)?),
+        }
+    }
+
+    if ret.is_empty() {
+        return Err("no stacks to solve".to_string());
+    }
+    Ok(ret)
+}
+
+/// Writes the summary of a sweep as CSV.
+pub fn write_manifest(path: &str, entries: &[SweepEntry]) -> Result<()> {
+    let mut outfile = File::create(path)?;
+    writeln!(
+        outfile,
+        "stack,iterations,ev_sb,ev_bb,exploitability,seconds,file"
+    )?;
+    for entry in entries {
+        writeln!(
+            outfile,
+            "{},{},{},{},{},{:.1},{}",
+            entry.stack,
+            entry.iterations,
+            entry.ev[0],
+            entry.ev[1],
+            entry.exploitability,
+            entry.seconds,
+            entry.path
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints the summary of a sweep.
+pub fn print_summary(entries: &[SweepEntry]) {
+    println!();
+    println!(
+        "{:>7} {:>10} {:>9} {:>9} {:>15} {:>9}",
+        "stack", "iterations", "EV(SB)", "EV(BB)", "exploitability", "time[s]"
+    );
+    for entry in entries {
+        println!(
+            "{:>5}bb {:>10} {:>+9.4} {:>+9.4} {:>+15.3e} {:>9.1}",
+            entry.stack,
+            entry.iterations,
+            entry.ev[0],
+            entry.ev[1],
+            entry.exploitability,
+            entry.seconds
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stacks() {
+        assert_eq!(parse_stacks("7, 10,15"), Ok(vec![7.0, 10.0, 15.0]));
+        assert_eq!(
+            parse_stacks("20..50:10,100"),
+            Ok(vec![20.0, 30.0, 40.0, 50.0, 100.0])
+        );
+        assert_eq!(parse_stacks("10..25:10"), Ok(vec![10.0, 20.0]));
+        assert_eq!(parse_stacks("12.5..12.5:1"), Ok(vec![12.5]));
+    }
+
+    #[test]
+    fn fractional_step() {
+        // each value is computed from the start, so the end is reached despite rounding errors
+        let stacks = parse_stacks("1..2:0.1").unwrap();
+        assert_eq!(stacks.len(), 11);
+        for (i, &stack) in stacks.iter().enumerate() {
+            assert_eq!(stack, 1.0 + i as f64 * 0.1);
+        }
+    }
+
+    #[test]
+    fn invalid_stacks() {
+        assert!(parse_stacks("").is_err());
+        assert!(parse_stacks("10,x").is_err());
+        assert!(parse_stacks("0").is_err());
+        assert!(parse_stacks("-5").is_err());
+        assert!(parse_stacks("10..50").is_err());
+        assert!(parse_stacks("10..50:0").is_err());
+        assert_eq!(
+            parse_stacks("50..10:5"),
+            Err("no stacks to solve".to_string())
+        );
+    }
+}
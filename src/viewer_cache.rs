@@ -0,0 +1,103 @@
+use std::fs;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+
+const SUFFIX: &str = ".ev.bin";
+
+/// Returns the path of the cache entry for the source file `source` whose contents hash to `hash`,
+/// e.g., `preflop-40-1000.0123456789abcdef.ev.bin`.
+pub fn entry_path(cache_dir: &Path, source: &Path, hash: u64) -> PathBuf {
+    let stem = source.file_stem().unwrap().to_string_lossy();
+    cache_dir.join(format!("{}.{:016x}{}", stem, hash, SUFFIX))
+}
+
+/// Returns the stem of the source file of the cache entry named `name`.
+fn source_stem(name: &str) -> Option<&str> {
+    let (stem, hash) = name.strip_suffix(SUFFIX)?.rsplit_once('.')?;
+    let is_hash = hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit());
+    (is_hash && !stem.is_empty()).then_some(stem)
+}
+
+/// Removes the cache entries whose source file in `source_dir` is gone, as well as the files
+/// that are not cache entries.
+pub fn prune(cache_dir: &Path, source_dir: &Path) -> Result<()> {
+    if !cache_dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(cache_dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap().to_string_lossy();
+        let keep = match source_stem(&name) {
+            Some(stem) => source_dir.join(format!("{}.bin", stem)).exists(),
+            None => false,
+        };
+        if !keep && path.is_file() {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the cache entry `path` and removes the other entries of the same source file, which
+/// were computed from its previous contents.
+pub fn store(path: &Path, bytes: &[u8]) -> Result<()> {
+    let cache_dir = path.parent().unwrap();
+    fs::create_dir_all(cache_dir)?;
+    let name = path.file_name().unwrap().to_string_lossy();
+    let stem = source_stem(&name).unwrap();
+    for entry in fs::read_dir(cache_dir)? {
+        let other = entry?.path();
+        let other_name = other.file_name().unwrap().to_string_lossy();
+        if other != path && source_stem(&other_name) == Some(stem) {
+            fs::remove_file(&other)?;
+        }
+    }
+    fs::write(path, bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        let path = entry_path(
+            Path::new("cache"),
+            Path::new("out/preflop-40-1000.bin"),
+            0xabc,
+        );
+        assert_eq!(
+            path,
+            Path::new("cache/preflop-40-1000.0000000000000abc.ev.bin")
+        );
+        let name = path.file_name().unwrap().to_str().unwrap();
+        assert_eq!(source_stem(name), Some("preflop-40-1000"));
+        assert_eq!(source_stem("preflop-40-1000.abc.ev.bin"), None);
+        assert_eq!(source_stem("ev-0000000000000abc.bin"), None);
+        assert_eq!(source_stem(".0000000000000abc.ev.bin"), None);
+    }
+
+    #[test]
+    fn prune_and_store() {
+        let dir = std::env::temp_dir().join(format!("poker-cfr-cache-{}", std::process::id()));
+        let cache_dir = dir.join("cache");
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(dir.join("preflop-40-1000.bin"), b"").unwrap();
+
+        let kept = entry_path(&cache_dir, Path::new("preflop-40-1000.bin"), 1);
+        let orphan = entry_path(&cache_dir, Path::new("preflop-20-1000.bin"), 2);
+        let stray = cache_dir.join("ev-0000000000000003.bin");
+        for path in [&kept, &orphan, &stray] {
+            fs::write(path, b"").unwrap();
+        }
+        prune(&cache_dir, &dir).unwrap();
+        assert!(kept.exists() && !orphan.exists() && !stray.exists());
+
+        let updated = entry_path(&cache_dir, Path::new("preflop-40-1000.bin"), 4);
+        store(&updated, b"ev").unwrap();
+        assert!(!kept.exists());
+        assert_eq!(fs::read(&updated).unwrap(), b"ev");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,269 @@
+use crossterm::style::Color;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Result, Write};
+use std::path::{Path, PathBuf};
+
+pub enum Format {
+    Text,
+    Csv,
+    Html,
+}
+
+/// Contents of the grid shown by the viewer. `cells` are stored in display order (e.g., the first
+/// row and column correspond to aces in hold'em).
+pub struct Grid {
+    pub title: String,
+    pub subtitle: String,
+    pub row_labels: Vec<String>,
+    pub col_labels: Vec<String>,
+    /// (displayed text, numerical value, foreground color)
+    pub cells: Vec<Vec<(String, f64, Color)>>,
+    pub footer: Vec<String>,
+}
+
+impl Format {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "t" | "txt" | "text" => Some(Format::Text),
+            "c" | "csv" => Some(Format::Csv),
+            "h" | "htm" | "html" => Some(Format::Html),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Text => "txt",
+            Format::Csv => "csv",
+            Format::Html => "html",
+        }
+    }
+}
+
+/// Writes `grid` into `dir` and returns the path of the written file. An existing file is never
+/// overwritten; a numeric suffix is appended to the name instead (e.g., `40bb_R2.5-2.txt`).
+pub fn export(grid: &Grid, format: &Format, dir: &str) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let content = match format {
+        Format::Text => to_text(grid),
+        Format::Csv => to_csv(grid),
+        Format::Html => to_html(grid),
+    };
+
+    let base_name = file_name(&grid.title);
+    for n in 1.. {
+        let name = match n {
+            1 => base_name.clone(),
+            _ => format!("{}-{}", base_name, n),
+        };
+        let path = Path::new(dir).join(format!("{}.{}", name, format.extension()));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut outfile) => {
+                outfile.write_all(content.as_bytes())?;
+                return Ok(path);
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!()
+}
+
+/// Converts a title like `10/25bb: R2.5 C` to a file name like `10-25bb_R2.5_C`. Path separators
+/// and colons become hyphens (trailing ones are dropped) and whitespace becomes underscores.
+fn file_name(title: &str) -> String {
+    title
+        .split_whitespace()
+        .map(|word| word.replace([':', '/', '\\'], "-"))
+        .map(|word| word.trim_matches('-').to_string())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn to_text(grid: &Grid) -> String {
+    let mut ret = format!("{}\n{}\n\n", grid.title, grid.subtitle);
+    let mut header = " |".to_string();
+    for label in &grid.col_labels {
+        header.push_str(&format!("{:>4}  ", label));
+    }
+    ret.push_str(header.trim_end());
+    ret.push('\n');
+    ret.push_str(&format!("-+{}\n", "-".repeat(6 * grid.col_labels.len())));
+    for (i, row) in grid.cells.iter().enumerate() {
+        let mut line = format!("{}|", grid.row_labels[i]);
+        for (text, _, _) in row {
+            line.push_str(&format!("{:<6}", text));
+        }
+        ret.push_str(line.trim_end());
+        ret.push('\n');
+    }
+    ret.push('\n');
+    for line in &grid.footer {
+        ret.push_str(line);
+        ret.push('\n');
+    }
+    ret
+}
+
+fn to_csv(grid: &Grid) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+    let mut ret = format!("{},{}\n", quote(&grid.title), quote(&grid.subtitle));
+    ret.push_str(&format!(",{}\n", grid.col_labels.join(",")));
+    for (i, row) in grid.cells.iter().enumerate() {
+        let values = row
+            .iter()
+            .map(|(_, value, _)| {
+                if value.is_nan() {
+                    String::new()
+                } else {
+                    format!("{:.4}", value)
+                }
+            })
+            .collect::<Vec<_>>();
+        ret.push_str(&format!(
+            "{},{}\n",
+            grid.row_labels[i].trim(),
+            values.join(",")
+        ));
+    }
+    for line in &grid.footer {
+        ret.push_str(&format!("{}\n", quote(line)));
+    }
+    ret
+}
+
+fn to_html(grid: &Grid) -> String {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+
+    let mut ret = String::new();
+    ret.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    ret.push_str(&format!("<title>{}</title>\n", escape(&grid.title)));
+    ret.push_str(
+        "<style>\n\
+         body { background: #000000; color: #e5e5e5; font-family: monospace; }\n\
+         table { border-collapse: collapse; }\n\
+         th, td { padding: 2px 6px; text-align: center; white-space: pre; }\n\
+         th { border-bottom: 1px solid #e5e5e5; }\n\
+         th.rank { border-bottom: none; border-right: 1px solid #e5e5e5; }\n\
+         tr:nth-child(odd) td, tr:nth-child(odd) th.rank { background: #262626; }\n\
+         </style>\n",
+    );
+    ret.push_str("</head>\n<body>\n");
+    ret.push_str(&format!("<h3>{}</h3>\n", escape(&grid.title)));
+    ret.push_str(&format!("<p>{}</p>\n", escape(&grid.subtitle)));
+
+    ret.push_str("<table>\n<tr><th></th>");
+    for label in &grid.col_labels {
+        ret.push_str(&format!("<th>{}</th>", escape(label)));
+    }
+    ret.push_str("</tr>\n");
+
+    for (i, row) in grid.cells.iter().enumerate() {
+        let label = escape(&grid.row_labels[i]);
+        ret.push_str(&format!("<tr><th class=\"rank\">{}</th>", label));
+        for (text, _, color) in row {
+            ret.push_str(&format!(
+                "<td style=\"color: {}\">{}</td>",
+                css_color(*color),
+                escape(text.trim())
+            ));
+        }
+        ret.push_str("</tr>\n");
+    }
+    ret.push_str("</table>\n");
+
+    ret.push_str("<pre>\n");
+    for line in &grid.footer {
+        ret.push_str(&escape(line));
+        ret.push('\n');
+    }
+    ret.push_str("</pre>\n</body>\n</html>\n");
+    ret
+}
+
+/// Converts a terminal color to the corresponding CSS color (xterm palette).
+fn css_color(color: Color) -> &'static str {
+    match color {
+        Color::Red => "#cd0000",
+        Color::Green => "#00cd00",
+        Color::Yellow => "#cdcd00",
+        Color::Magenta => "#cd00cd",
+        Color::Cyan => "#00cdcd",
+        _ => "#e5e5e5",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid {
+        let mut cells = vec![vec![(String::new(), f64::NAN, Color::Reset); 13]; 13];
+        cells[0][0] = ("R 100%".to_string(), 1.0, Color::Red);
+        cells[12][1] = ("<F> & C".to_string(), 0.25, Color::Cyan);
+        let ranks = "AKQJT98765432".chars().map(|c| c.to_string());
+        Grid {
+            title: "10/25bb: R2.5 C".to_string(),
+            subtitle: "SB, \"raise\" rate".to_string(),
+            row_labels: ranks.clone().collect(),
+            col_labels: ranks.collect(),
+            cells,
+            footer: vec!["- EV: +0.1".to_string()],
+        }
+    }
+
+    #[test]
+    fn file_names() {
+        assert_eq!(file_name("10/25bb: R2.5 C"), "10-25bb_R2.5_C");
+        assert_eq!(file_name("40bb:"), "40bb");
+        assert_eq!(file_name("a\\b:c  d"), "a-b-c_d");
+        assert_ne!(file_name("10/25bb: C"), file_name("1025bb: C"));
+    }
+
+    #[test]
+    fn text() {
+        let text = to_text(&grid());
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "10/25bb: R2.5 C");
+        assert_eq!(lines[5], "A|R 100%");
+        assert_eq!(lines[17], "2|      <F> & C");
+        assert_eq!(lines.last(), Some(&"- EV: +0.1"));
+    }
+
+    #[test]
+    fn csv() {
+        let csv = to_csv(&grid());
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "\"10/25bb: R2.5 C\",\"SB, \"\"raise\"\" rate\"");
+        assert_eq!(lines[1], ",A,K,Q,J,T,9,8,7,6,5,4,3,2");
+        assert_eq!(lines[2], "A,1.0000,,,,,,,,,,,,");
+        assert_eq!(lines[14], "2,,0.2500,,,,,,,,,,,");
+        assert_eq!(lines[15], "\"- EV: +0.1\"");
+    }
+
+    #[test]
+    fn html() {
+        let html = to_html(&grid());
+        assert!(html.contains("<title>10/25bb: R2.5 C</title>"));
+        assert!(html.contains("<td style=\"color: #cd0000\">R 100%</td>"));
+        assert!(html.contains("<td style=\"color: #00cdcd\">&lt;F&gt; &amp; C</td>"));
+        assert_eq!(html.matches("<tr>").count(), 14);
+    }
+
+    #[test]
+    fn no_overwrite() {
+        let dir = std::env::temp_dir().join(format!("poker-cfr-export-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        let first = export(&grid(), &Format::Csv, dir).unwrap();
+        let second = export(&grid(), &Format::Csv, dir).unwrap();
+        assert_eq!(first.file_name().unwrap(), "10-25bb_R2.5_C.csv");
+        assert_eq!(second.file_name().unwrap(), "10-25bb_R2.5_C-2.csv");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
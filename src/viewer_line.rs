@@ -0,0 +1,206 @@
+use crate::game_info::action_matches;
+
+/// Rank characters in ascending order (deuce to ace).
+const RANK_CHARS: &str = "23456789TJQKA";
+
+/// Splits a line such as `40bb: R2.5 R3 R4 C` into the file part before `:` (`None` when
+/// omitted) and the action tokens.
+pub fn split_line(input: &str) -> (Option<&str>, Vec<&str>) {
+    let (file, actions) = match input.find(':') {
+        Some(pos) => (Some(input[..pos].trim()), &input[pos + 1..]),
+        None => (None, input),
+    };
+    (file, actions.split_whitespace().collect())
+}
+
+/// Parses a file part such as `40`, `40bb`, `10/25bb` (SB/BB) or `40bb-ante0.1` into the stacks
+/// of SB and BB and the tag of the blinds following `bb` (empty for the default blinds).
+pub fn parse_file(input: &str) -> Result<([f64; 2], &str), String> {
+    let input = input.trim();
+    let (stack, tag) = match input.find("bb") {
+        Some(pos) => (&input[..pos], &input[pos + 2..]),
+        None => (input, ""),
+    };
+    let (sb_stack, bb_stack) = match stack.find('/') {
+        Some(pos) => (&stack[..pos], &stack[pos + 1..]),
+        None => (stack, stack),
+    };
+    let parse_stack = |s: &str| {
+        s.parse::<f64>()
+            .map_err(|_| format!("invalid stack '{}'", stack))
+    };
+    Ok(([parse_stack(sb_stack)?, parse_stack(bb_stack)?], tag))
+}
+
+/// Converts action tokens to action indices. `labels` returns the labels of the actions available
+/// after the given actions, or `None` after a terminal action. `translate` maps a token that
+/// matches no label to an action and a note describing the translation. Returns the actions and
+/// the notes.
+pub fn parse_actions(
+    tokens: &[&str],
+    labels: impl Fn(&[usize]) -> Option<Vec<String>>,
+    translate: impl Fn(&[usize], &str) -> Option<(usize, String)>,
+) -> Result<(Vec<usize>, Vec<String>), String> {
+    let mut actions = Vec::new();
+    let mut notes = Vec::new();
+    for token in tokens {
+        let labels = labels(&actions).ok_or(format!("'{}' follows a terminal action", token))?;
+        let action = match labels.iter().position(|label| action_matches(token, label)) {
+            Some(action) => action,
+            None => {
+                let (action, note) =
+                    translate(&actions, token).ok_or(format!("invalid action '{}'", token))?;
+                notes.push(note);
+                action
+            }
+        };
+        actions.push(action);
+    }
+    Ok((actions, notes))
+}
+
+/// Parses a hand class such as `A5s`, `KQo` or `TT` and returns its summarized index.
+pub fn parse_hand_class(input: &str) -> Option<(usize, usize)> {
+    let chars = input
+        .trim()
+        .to_ascii_uppercase()
+        .chars()
+        .collect::<Vec<_>>();
+    let rank = |c: char| RANK_CHARS.find(c);
+    match chars.as_slice() {
+        [c1, c2] if c1 == c2 => rank(*c1).map(|r| (r, r)),
+        [c1, c2, s] if c1 != c2 && (*s == 'S' || *s == 'O') => {
+            let (r1, r2) = (rank(*c1)?, rank(*c2)?);
+            let (lo, hi) = (r1.min(r2), r1.max(r2));
+            Some(if *s == 'S' { (lo, hi) } else { (hi, lo) })
+        }
+        _ => None,
+    }
+}
+
+/// Returns the combos (pairs of cards in ascending order) of the hand class `[rank1][rank2]`.
+/// `rank1 < rank2` means suited hands and `rank1 > rank2` means offsuit hands.
+pub fn class_combos(rank1: usize, rank2: usize) -> Vec<(usize, usize)> {
+    let mut ret = Vec::new();
+    for suit1 in 0..4 {
+        for suit2 in 0..4 {
+            let keep = match rank1.cmp(&rank2) {
+                std::cmp::Ordering::Equal => suit1 < suit2,
+                std::cmp::Ordering::Less => suit1 == suit2,
+                std::cmp::Ordering::Greater => suit1 != suit2,
+            };
+            if keep {
+                let lo = rank1.min(rank2);
+                let hi = rank1.max(rank2);
+                ret.push((4 * lo + suit1, 4 * hi + suit2));
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(labels: &[&str]) -> Option<Vec<String>> {
+        Some(labels.iter().map(|label| label.to_string()).collect())
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(
+            split_line("40bb: R2.5 R3  C"),
+            (Some("40bb"), vec!["R2.5", "R3", "C"])
+        );
+        assert_eq!(split_line(" A C "), (None, vec!["A", "C"]));
+        assert_eq!(split_line("10:"), (Some("10"), vec![]));
+    }
+
+    #[test]
+    fn file() {
+        assert_eq!(parse_file(" 40bb"), Ok(([40.0, 40.0], "")));
+        assert_eq!(parse_file("12.5"), Ok(([12.5, 12.5], "")));
+        assert_eq!(parse_file("20bb-ante0.1"), Ok(([20.0, 20.0], "-ante0.1")));
+        assert_eq!(parse_file("10/25bb"), Ok(([10.0, 25.0], "")));
+        assert!(parse_file("40x").is_err());
+        assert!(parse_file("10/bb").is_err());
+        assert!(parse_file("bb-ante0.1").is_err());
+    }
+
+    #[test]
+    fn actions() {
+        // root: Fold/Call/Bet 2.5x/All-in, after a call: Check/All-in, after all-in: Fold/Call
+        let tree = |actions: &[usize]| match actions {
+            [] => labels(&["Fold", "Call", "Bet 2.5x", "All-in"]),
+            [1] => labels(&["Check", "All-in"]),
+            [2] | [3] | [1, 1] => labels(&["Fold", "Call"]),
+            _ => None,
+        };
+        let none = |_: &[usize], _: &str| None;
+        let ok = |actions: Vec<usize>| Ok((actions, vec![]));
+        assert_eq!(parse_actions(&["r2.5", "c"], tree, none), ok(vec![2, 1]));
+        assert_eq!(
+            parse_actions(&["C", "jam", "F"], tree, none),
+            ok(vec![1, 1, 0])
+        );
+        assert_eq!(parse_actions(&["call", "x"], tree, none), ok(vec![1, 0]));
+        assert_eq!(parse_actions(&[], tree, none), ok(vec![]));
+        assert_eq!(
+            parse_actions(&["R3"], tree, none),
+            Err("invalid action 'R3'".to_string())
+        );
+        assert_eq!(
+            parse_actions(&["F", "C"], tree, none),
+            Err("'C' follows a terminal action".to_string())
+        );
+
+        // off-tree raises at the root are translated to the 2.5x raise
+        let translate = |actions: &[usize], token: &str| match actions {
+            [] if token.starts_with('R') => Some((2, format!("{} => Bet 2.5x", token))),
+            _ => None,
+        };
+        assert_eq!(
+            parse_actions(&["R3", "C"], tree, translate),
+            Ok((vec![2, 1], vec!["R3 => Bet 2.5x".to_string()]))
+        );
+        assert_eq!(
+            parse_actions(&["C", "R3"], tree, translate),
+            Err("invalid action 'R3'".to_string())
+        );
+    }
+
+    #[test]
+    fn matches() {
+        assert!(action_matches("B3X", "Bet 3x"));
+        assert!(action_matches("3", "Bet 3x"));
+        assert!(!action_matches("R3.5", "Bet 3x"));
+        assert!(action_matches("k", "Check"));
+        assert!(!action_matches("k", "Call"));
+        assert!(action_matches("shove", "All-in"));
+    }
+
+    #[test]
+    fn hand_class() {
+        assert_eq!(parse_hand_class("AA"), Some((12, 12)));
+        assert_eq!(parse_hand_class("a5s"), Some((3, 12)));
+        assert_eq!(parse_hand_class("5Ao"), Some((12, 3)));
+        assert_eq!(parse_hand_class(" KQo "), Some((11, 10)));
+        assert_eq!(parse_hand_class("AKx"), None);
+        assert_eq!(parse_hand_class("AAs"), None);
+        assert_eq!(parse_hand_class("A1s"), None);
+    }
+
+    #[test]
+    fn combos() {
+        let pairs = class_combos(12, 12);
+        let suited = class_combos(3, 12);
+        let offsuit = class_combos(12, 3);
+        assert_eq!((pairs.len(), suited.len(), offsuit.len()), (6, 4, 12));
+        assert!(pairs.iter().all(|&(c1, c2)| c1 / 4 == 12 && c2 / 4 == 12));
+        assert!(suited.iter().all(|&(c1, c2)| c1 % 4 == c2 % 4 && c1 < c2));
+        assert!(offsuit
+            .iter()
+            .all(|&(c1, c2)| c1 % 4 != c2 % 4 && (c1 / 4, c2 / 4) == (3, 12)));
+    }
+}
@@ -0,0 +1,380 @@
+use crate::game_info::{self, GameInfo, GameSpec};
+use crate::game_node::*;
+use crate::game_preflop::{PreflopAction, PreflopNode};
+use crate::preflop_equity::*;
+use bincode::deserialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Result};
+use std::path::Path;
+
+pub type Strategy = HashMap<PublicInfoSet, Vec<Vec<f64>>>;
+
+/// Strategy of a pre-flop game to start training from.
+pub struct Seed {
+    pub strategy: Strategy,
+    /// Root of the seed's tree, or `None` for files without metadata, whose trees have the default
+    /// limp options
+    pub root: Option<PreflopNode>,
+}
+
+/// Loads a strategy from an output file of the pre-flop solver: either a combo-level strategy
+/// (`*.combo.bin`) or a summarized one (`*.bin`, including files written before the combo-level
+/// strategy was kept), whose 13x13 grids are expanded to combos.
+pub fn load_strategy(path: &str) -> Result<Strategy> {
+    let mut infile = File::open(path)?;
+    let mut buf = Vec::new();
+    infile.read_to_end(&mut buf)?;
+    let invalid = |_| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid strategy file '{}'", path),
+        )
+    };
+
+    if path.ends_with(".combo.bin") {
+        return deserialize::<Strategy>(&buf).map_err(invalid);
+    }
+
+    // newer files have the rake as the fourth element, which is ignored here
+    let (summarized, _, _) =
+        deserialize::<(HashMap<PublicInfoSet, Vec<Vec<Vec<f64>>>>, f64, f64)>(&buf)
+            .map_err(invalid)?;
+    Ok(summarized
+        .iter()
+        .map(|(key, value)| (key.clone(), restore_strategy(value)))
+        .collect())
+}
+
+/// Loads the combo-level strategy of the output file at `path`, which is read from the
+/// `.combo.bin` file if any (or restored from the summarized strategy otherwise).
+pub fn load_combo_strategy(path: &str) -> Result<Strategy> {
+    let combo_path = format!("{}.combo.bin", path.trim_end_matches(".bin"));
+    if Path::new(&combo_path).exists() {
+        load_strategy(&combo_path)
+    } else {
+        load_strategy(path)
+    }
+}
+
+/// Loads the strategy of the output file at `path` (see `load_strategy`) together with the root of
+/// its tree, which is rebuilt from the metadata of the summarized file.
+pub fn load_seed(path: &str) -> Result<Seed> {
+    let strategy = load_strategy(path)?;
+    let info_path = format!(
+        "{}.bin",
+        path.trim_end_matches(".bin").trim_end_matches(".combo")
+    );
+    let root = match game_info::load_info(&info_path) {
+        Ok(GameInfo {
+            spec:
+                GameSpec::Preflop {
+                    stacks,
+                    blinds,
+                    rake,
+                    limp,
+                },
+            ..
+        }) => Some(
+            PreflopNode::with_stacks(stacks, blinds)
+                .with_rake(rake)
+                .with_limp(limp),
+        ),
+        Ok(_) => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("'{}' is not a pre-flop solution", path),
+            ))
+        }
+        // written by an older version
+        Err(_) => None,
+    };
+    Ok(Seed { strategy, root })
+}
+
+/// Maps `seed`, a strategy of a pre-flop game with possibly different stacks or blinds, onto the
+/// tree rooted at `root`. Since the stacks determine which bet sizes are available, each action is
+/// translated to the action of the same size in the seed's tree, where all-in corresponds to
+/// all-in and sizes missing in the seed to the nearest available one (see `translate_action`).
+/// Actions without a counterpart in the other tree start with probability zero, or have their
+/// probability merged into the nearest action. The strategy is converted between combos and hand
+/// classes as needed. The actions of the seed are taken from its tree, which may have other limp
+/// options (see `Limp`).
+pub fn map_strategy(root: &PreflopNode, seed: &Seed) -> Strategy {
+    let mut ret = HashMap::new();
+    map_node(root, Some(Vec::new()), seed, &mut ret);
+    ret
+}
+
+fn map_node(node: &PreflopNode, seed_key: Option<PublicInfoSet>, seed: &Seed, ret: &mut Strategy) {
+    if node.is_terminal_node() {
+        return;
+    }
+
+    let kinds = node
+        .actions()
+        .map(|action| node.action(action))
+        .collect::<Vec<_>>();
+    let seed_strategy = seed_key
+        .as_ref()
+        .and_then(|key| seed.strategy.get(key))
+        .and_then(|strategy| convert_level(strategy, node.private_info_set_len()));
+    let seed_kinds = seed_strategy
+        .as_ref()
+        .and_then(|strategy| match &seed.root {
+            Some(seed_root) => {
+                let seed_node = seed_key
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .fold(seed_root.clone(), |seed_node, &action| {
+                        seed_node.play(action as usize)
+                    });
+                let kinds = seed_node.actions().map(|action| seed_node.action(action));
+                Some(kinds.collect::<Vec<_>>()).filter(|kinds| kinds.len() == strategy.len())
+            }
+            None => Some(
+                (0..strategy.len())
+                    .map(|action| PreflopNode::action_kind(action, strategy.len()))
+                    .collect(),
+            ),
+        });
+
+    if let (Some(strategy), Some(seed_kinds)) = (&seed_strategy, &seed_kinds) {
+        let mut mapped = vec![vec![0.0; node.private_info_set_len()]; kinds.len()];
+        for (value, &kind) in strategy.iter().zip(seed_kinds) {
+            let target = translate_action(kind, &kinds);
+            mapped[target]
+                .iter_mut()
+                .zip(value)
+                .for_each(|(m, v)| *m += v);
+        }
+        ret.insert(node.public_info_set().clone(), mapped);
+    }
+
+    for action in node.actions() {
+        let child_key = seed_kinds.as_ref().map(|seed_kinds| {
+            let mut key = seed_key.clone().unwrap();
+            key.push(translate_action(kinds[action], seed_kinds) as u8);
+            key
+        });
+        map_node(&node.play(action), child_key, seed, ret);
+    }
+}
+
+/// Returns the index of the action among `to_kinds` that corresponds to `kind`: the action of the
+/// same kind, or for a raise missing in `to_kinds`, the raise of the nearest multiple. A limp
+/// missing in `to_kinds` (without limps) becomes the smallest raise. Without any raise, a raise
+/// becomes all-in, and an all-in facing a bet that has put the stack in becomes a call.
+fn translate_action(kind: PreflopAction, to_kinds: &[PreflopAction]) -> usize {
+    if let Some(index) = to_kinds.iter().position(|&to_kind| to_kind == kind) {
+        return index;
+    }
+    // a limp is matched like a raise to the current bet
+    let multiple = match kind {
+        PreflopAction::Raise(multiple) => Some(multiple),
+        PreflopAction::Call => Some(1.0),
+        _ => None,
+    };
+    let nearest_raise = multiple.and_then(|multiple| {
+        to_kinds
+            .iter()
+            .enumerate()
+            .filter_map(|(index, to_kind)| match to_kind {
+                PreflopAction::Raise(to_multiple) => Some((index, (to_multiple - multiple).abs())),
+                _ => None,
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(index, _)| index)
+    });
+    nearest_raise
+        .or_else(|| {
+            to_kinds
+                .iter()
+                .position(|&to_kind| to_kind == PreflopAction::AllIn)
+        })
+        .unwrap_or(1)
+}
+
+/// Converts the strategy of each action to `len` elements (combos or hand classes).
+fn convert_level(strategy: &[Vec<f64>], len: usize) -> Option<Vec<Vec<f64>>> {
+    match (strategy[0].len(), len) {
+        (from, to) if from == to => Some(strategy.to_vec()),
+        (NUM_COMBOS, NUM_CLASSES) => Some(average_classes(strategy)),
+        (NUM_CLASSES, NUM_COMBOS) => Some(expand_classes(strategy)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limp::Limp;
+
+    /// Returns a class-level strategy of the tree rooted at `node` whose probabilities differ
+    /// between actions and information sets.
+    fn strategy(node: &PreflopNode, ret: &mut Strategy) {
+        if node.is_terminal_node() {
+            return;
+        }
+        let key = node.public_info_set();
+        let offset = key.iter().map(|&action| action as f64).sum::<f64>();
+        let weights = node
+            .actions()
+            .map(|action| action as f64 + 1.0 + offset)
+            .collect::<Vec<_>>();
+        let total = weights.iter().sum::<f64>();
+        let value = weights
+            .iter()
+            .map(|weight| vec![weight / total; NUM_CLASSES])
+            .collect();
+        ret.insert(key.clone(), value);
+        for action in node.actions() {
+            strategy(&node.play(action), ret);
+        }
+    }
+
+    /// Returns a seed with the strategy of `strategy` over the tree rooted at `root`, whose root is
+    /// known when `with_root` is set.
+    fn seed(root: PreflopNode, with_root: bool) -> Seed {
+        let mut ret = Strategy::new();
+        strategy(&root, &mut ret);
+        Seed {
+            strategy: ret,
+            root: Some(root).filter(|_| with_root),
+        }
+    }
+
+    /// Returns the probabilities of the first hand class of the strategy at `key`.
+    fn probs(strategy: &Strategy, key: &[u8]) -> Vec<f64> {
+        strategy[key].iter().map(|value| value[0]).collect()
+    }
+
+    fn assert_probs(lhs: &[f64], rhs: &[f64]) {
+        assert_eq!(lhs.len(), rhs.len(), "{:?} != {:?}", lhs, rhs);
+        for (l, r) in lhs.iter().zip(rhs) {
+            assert!((l - r).abs() < 1e-12, "{:?} != {:?}", lhs, rhs);
+        }
+    }
+
+    #[test]
+    fn action_kinds() {
+        // 2.5x at 10bb leaves a ratio of 4: the 4x raise is all-in
+        let node = PreflopNode::new(10.0).play(2);
+        let kinds = node.actions().map(|action| node.action(action));
+        assert_eq!(
+            kinds.collect::<Vec<_>>(),
+            [
+                PreflopAction::Fold,
+                PreflopAction::Call,
+                PreflopAction::Raise(2.5),
+                PreflopAction::Raise(3.0),
+                PreflopAction::Raise(3.5),
+                PreflopAction::AllIn
+            ]
+        );
+        assert_eq!(node.play(5).play(1).public_info_set(), &vec![2, 5, 1]);
+    }
+
+    #[test]
+    fn translate() {
+        use PreflopAction::*;
+        let kinds = [Fold, Call, Raise(2.5), Raise(3.0), AllIn];
+        assert_eq!(translate_action(Call, &kinds), 1);
+        assert_eq!(translate_action(Raise(3.0), &kinds), 3);
+        assert_eq!(translate_action(Raise(4.0), &kinds), 3);
+        assert_eq!(translate_action(AllIn, &kinds), 4);
+        assert_eq!(translate_action(Raise(2.5), &[Fold, Call, AllIn]), 2);
+        assert_eq!(translate_action(AllIn, &[Fold, Call]), 1);
+        assert_eq!(
+            translate_action(Call, &[Fold, Raise(2.5), Raise(3.0), AllIn]),
+            1
+        );
+        assert_eq!(translate_action(Call, &[Fold, AllIn]), 1);
+    }
+
+    #[test]
+    fn shallower_from_deeper() {
+        // a seed without metadata has the default tree
+        let seed = seed(PreflopNode::new_isomorphic(20.0), false);
+        let mapped = map_strategy(&PreflopNode::new_isomorphic(10.0), &seed);
+        let seed = seed.strategy;
+
+        // same actions at the root
+        assert_probs(&probs(&mapped, &[]), &probs(&seed, &[]));
+
+        // after 2.5x: 4x merges into 3.5x, which is the largest raise below 10bb
+        let s = probs(&seed, &[2]);
+        assert_probs(
+            &probs(&mapped, &[2]),
+            &[s[0], s[1], s[2], s[3], s[4] + s[5], s[6]],
+        );
+
+        // after 2.5x twice: no raise is left below 10bb, so the raises merge into all-in
+        let s = probs(&seed, &[2, 2]);
+        assert_probs(&probs(&mapped, &[2, 2]), &[s[0], s[1], s[2] + s[3] + s[4]]);
+
+        // all-in follows the seed's all-in, and 3.5x the seed's 3.5x
+        assert_probs(&probs(&mapped, &[2, 2, 2]), &probs(&seed, &[2, 2, 4]));
+        assert_probs(&probs(&mapped, &[2, 4]), &probs(&seed, &[2, 4]));
+
+        for strategy in mapped.values() {
+            let total = strategy.iter().map(|value| value[0]).sum::<f64>();
+            assert!((total - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn deeper_from_shallower() {
+        let seed = seed(PreflopNode::new_isomorphic(10.0), true);
+        let mapped = map_strategy(&PreflopNode::new_isomorphic(20.0), &seed);
+        let seed = seed.strategy;
+
+        // after 2.5x: 4x is missing in the seed and starts with probability zero
+        let s = probs(&seed, &[2]);
+        assert_probs(
+            &probs(&mapped, &[2]),
+            &[s[0], s[1], s[2], s[3], s[4], 0.0, s[5]],
+        );
+
+        // after 2.5x twice: the seed only has all-in
+        let s = probs(&seed, &[2, 2]);
+        assert_probs(&probs(&mapped, &[2, 2]), &[s[0], s[1], 0.0, 0.0, s[2]]);
+
+        // 4x follows the seed's 3.5x, and 2.5x without any raise in the seed its all-in
+        assert_probs(&probs(&mapped, &[2, 5]), &probs(&seed, &[2, 4]));
+        let s = probs(&seed, &[2, 2, 2]);
+        assert_probs(&probs(&mapped, &[2, 2, 2]), &[s[0], s[1], 0.0]);
+    }
+
+    #[test]
+    fn other_limp_options() {
+        let no_limp = Limp {
+            allowed: false,
+            ..Limp::default()
+        };
+        let root = PreflopNode::new_isomorphic(20.0);
+        let no_limp_root = PreflopNode::new_isomorphic(20.0).with_limp(no_limp);
+
+        // without limps in the target, the seed's limp merges into the smallest raise
+        let default_seed = seed(root.clone(), true);
+        let mapped = map_strategy(&no_limp_root, &default_seed);
+        let s = probs(&default_seed.strategy, &[]);
+        assert_probs(
+            &probs(&mapped, &[]),
+            &[s[0], s[1] + s[2], s[3], s[4], s[5], s[6]],
+        );
+
+        // without limps in the seed, the target's limp starts with probability zero and follows
+        // the seed's smallest raise, which faces the same sizes
+        let no_limp_seed = seed(no_limp_root, true);
+        let mapped = map_strategy(&root, &no_limp_seed);
+        let s = probs(&no_limp_seed.strategy, &[]);
+        assert_probs(
+            &probs(&mapped, &[]),
+            &[s[0], 0.0, s[1], s[2], s[3], s[4], s[5]],
+        );
+        assert_probs(&probs(&mapped, &[1]), &probs(&no_limp_seed.strategy, &[1]));
+        assert_probs(&probs(&mapped, &[2]), &probs(&no_limp_seed.strategy, &[1]));
+    }
+}